@@ -0,0 +1,11 @@
+------------------
+-- Subscription --
+------------------
+
+-- Subscribing to a feed twice returns the existing subscription, so remove any duplicates
+DELETE FROM user_feed AS duplicate USING user_feed
+WHERE duplicate.owner = user_feed.owner
+  AND duplicate.feed = user_feed.feed
+  AND duplicate.id > user_feed.id;
+
+ALTER TABLE user_feed ADD CONSTRAINT user_feed_owner_feed UNIQUE (owner, feed);
@@ -0,0 +1,34 @@
+---------
+-- Job --
+---------
+
+CREATE TYPE job_status AS ENUM ('pending', 'running', 'completed', 'failed');
+
+-- Long running work queued by the API and run by the workers of the daemon. Jobs are claimed
+-- with SKIP LOCKED so any number of workers can share the queue, and failed attempts are
+-- retried at run_at until max_attempts is reached
+CREATE TABLE job (
+  id bigserial PRIMARY KEY,
+  owner integer REFERENCES "user" (id) ON DELETE CASCADE,
+
+  kind text NOT NULL,
+  payload jsonb NOT NULL,
+  status job_status NOT NULL DEFAULT 'pending',
+
+  progress integer NOT NULL DEFAULT 0,
+  total integer,
+  result jsonb,
+  error text,
+
+  attempts integer NOT NULL DEFAULT 0,
+  max_attempts integer NOT NULL DEFAULT 5,
+  run_at timestamptz NOT NULL DEFAULT NOW(),
+  started_at timestamptz,
+  finished_at timestamptz,
+
+  created_at timestamptz NOT NULL DEFAULT NOW(),
+  updated_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX job_queue_idx ON job (run_at) WHERE status IN ('pending', 'running');
+CREATE INDEX job_owner_idx ON job (owner);
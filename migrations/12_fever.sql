@@ -0,0 +1,13 @@
+---------------
+-- Fever Key --
+---------------
+
+-- Key of a user for the Fever API. Clients send md5("<email>:<password>") for a password the
+-- user picks for them, which is stored hashed again with SHA-256 so that the table can't be
+-- used to authenticate
+CREATE TABLE user_fever_key (
+  owner integer PRIMARY KEY REFERENCES "user" (id) ON DELETE CASCADE,
+  api_key_hash text NOT NULL UNIQUE,
+
+  created_at timestamptz NOT NULL DEFAULT NOW()
+);
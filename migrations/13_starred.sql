@@ -0,0 +1,9 @@
+-------------
+-- Starred --
+-------------
+
+-- When the user starred the item, which starred items are listed by
+ALTER TABLE user_item_state ADD COLUMN starred_at timestamptz;
+UPDATE user_item_state SET starred_at = updated_at WHERE starred;
+
+CREATE INDEX user_item_state_starred ON user_item_state (owner, starred_at DESC) WHERE starred;
@@ -0,0 +1,12 @@
+----------------
+--- Search -----
+----------------
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Trigram indexes support both ILIKE substring matches and similarity ranking
+CREATE INDEX feed_title_trgm ON feed USING gin (title gin_trgm_ops);
+CREATE INDEX feed_description_trgm ON feed USING gin (description gin_trgm_ops);
+CREATE INDEX feed_domain_trgm ON feed USING gin (domain gin_trgm_ops);
+
+CREATE INDEX user_feed_feed ON user_feed (feed);
@@ -0,0 +1,19 @@
+----------------
+-- Feed Icons --
+----------------
+
+CREATE TABLE feed_icon (
+  id serial PRIMARY KEY,
+  feed integer NOT NULL UNIQUE REFERENCES feed (id) ON DELETE CASCADE,
+
+  -- All null when no icon could be found for the feed's website
+  link text,
+  content_type text,
+  data bytea,
+  hash text, -- Hex encoded SHA-256 of data
+
+  fetched_at timestamptz NOT NULL DEFAULT NOW(),
+  created_at timestamptz NOT NULL DEFAULT NOW(),
+  updated_at timestamptz NOT NULL DEFAULT NOW()
+);
+CREATE INDEX feed_icon_fetched_at ON feed_icon (fetched_at);
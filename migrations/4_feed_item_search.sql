@@ -0,0 +1,11 @@
+-----------------
+-- Item Search --
+-----------------
+
+-- Kept up to date by Postgres on insert and update of the item
+ALTER TABLE feed_item ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+  setweight(to_tsvector('english', coalesce(title, '')), 'A') ||
+  setweight(to_tsvector('english', coalesce(description, '')), 'B') ||
+  setweight(to_tsvector('english', coalesce(content, '')), 'C')
+) STORED;
+CREATE INDEX feed_item_search_vector ON feed_item USING gin (search_vector);
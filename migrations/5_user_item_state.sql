@@ -0,0 +1,19 @@
+----------------
+-- Item State --
+----------------
+
+CREATE TABLE user_item_state (
+  id bigserial PRIMARY KEY,
+  owner integer NOT NULL REFERENCES "user" (id) ON DELETE CASCADE,
+  item bigint NOT NULL REFERENCES feed_item (id) ON DELETE CASCADE,
+
+  read boolean NOT NULL DEFAULT false,
+  read_at timestamptz,
+  starred boolean NOT NULL DEFAULT false,
+
+  created_at timestamptz NOT NULL DEFAULT NOW(),
+  updated_at timestamptz NOT NULL DEFAULT NOW(),
+
+  UNIQUE (owner, item)
+);
+CREATE INDEX user_item_state_item ON user_item_state (item);
@@ -0,0 +1,7 @@
+-------------------
+-- Search Fields --
+-------------------
+
+ALTER TABLE feed_item ADD COLUMN author text;
+
+CREATE INDEX feed_item_published_at ON feed_item (published_at DESC);
@@ -0,0 +1,16 @@
+----------------
+-- Sort Order --
+----------------
+
+CREATE TYPE sort_order AS ENUM (
+  'recently_updated',
+  'as_is',
+  'published',
+  'oldest',
+  'relevance',
+  'random'
+);
+
+ALTER TABLE user_index ALTER COLUMN sort TYPE sort_order USING (
+  CASE WHEN sort = 'as_is' THEN 'as_is' ELSE 'recently_updated' END
+)::sort_order;
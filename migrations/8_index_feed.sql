@@ -0,0 +1,12 @@
+----------------
+-- Index Feed --
+----------------
+
+-- Secret which allows reading an index as a feed without authentication. Deleting the row
+-- revokes access, and replacing the token revokes the URLs shared with the previous one
+CREATE TABLE user_index_feed (
+  index integer PRIMARY KEY REFERENCES user_index (id) ON DELETE CASCADE,
+  token text NOT NULL UNIQUE,
+
+  created_at timestamptz NOT NULL DEFAULT NOW()
+);
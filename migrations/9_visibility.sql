@@ -0,0 +1,19 @@
+----------------
+-- Visibility --
+----------------
+
+CREATE TYPE visibility AS ENUM ('private', 'unlisted', 'public');
+
+-- Existing indexes and lists get a random slug, which their owner can replace when sharing them
+ALTER TABLE user_index
+  ADD COLUMN visibility visibility NOT NULL DEFAULT 'private',
+  ADD COLUMN slug text NOT NULL UNIQUE DEFAULT substr(md5(random()::text), 1, 12);
+ALTER TABLE user_index ALTER COLUMN slug DROP DEFAULT;
+
+ALTER TABLE user_list
+  ADD COLUMN visibility visibility NOT NULL DEFAULT 'private',
+  ADD COLUMN slug text NOT NULL UNIQUE DEFAULT substr(md5(random()::text), 1, 12);
+ALTER TABLE user_list ALTER COLUMN slug DROP DEFAULT;
+
+CREATE INDEX user_index_public ON user_index (updated_at) WHERE visibility = 'public';
+CREATE INDEX user_list_public ON user_list (updated_at) WHERE visibility = 'public';
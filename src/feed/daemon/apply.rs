@@ -46,7 +46,8 @@ pub async fn apply_feed_update(
           updated_at = $13,
           fetched_at = $14,
           successful_fetch_at = $15,
-          next_fetch_at = $16
+          next_fetch_at = $16,
+          language = $17
         WHERE id = $1
         "#,
         feed.id,
@@ -76,6 +77,7 @@ pub async fn apply_feed_update(
             .successful_fetch_at
             .unwrap_or(feed.successful_fetch_at),
         feed_update.next_fetch_at.unwrap_or(feed.next_fetch_at),
+        feed_update.language.as_deref().or(feed.language.as_deref()),
     )
     .execute(&mut *tx)
     .await?;
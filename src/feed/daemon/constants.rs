@@ -2,3 +2,6 @@ use chrono::Duration;
 
 pub const MIN_TIME_BETWEEN_UPDATES: Duration = Duration::minutes(15);
 pub const MAX_TIME_BETWEEN_UPDATES: Duration = Duration::days(1);
+
+/// Maximum number of feeds to fetch icons for on each tick of the daemon
+pub const ICON_UPDATES_PER_TICK: i64 = 50;
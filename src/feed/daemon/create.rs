@@ -2,7 +2,7 @@ use chrono::Utc;
 
 use crate::feed::parser::utils::domain_from_link;
 use crate::feed::parser::{
-    feed_item::ParsedFeedItem, parse_feed_from_response, ParsedFromResponseError,
+    feed::ParsedFeed, feed_item::ParsedFeedItem, parse_feed_from_response, ParsedFromResponseError,
 };
 use crate::sql::InsertFeed;
 
@@ -62,3 +62,15 @@ pub async fn get_feed_creation(
         Err(err) => Err(FeedCreationError::OtherFetchError(err)),
     }
 }
+
+pub async fn get_feed_preview(
+    fetch: Result<FeedFetch, FeedFetchError>,
+) -> Result<ParsedFeed, FeedCreationError> {
+    match fetch {
+        Ok(FeedFetch::Modified(response)) => Ok(parse_feed_from_response(response).await?),
+        Ok(FeedFetch::NotModified(_)) => Err(FeedCreationError::NotModified),
+        Ok(FeedFetch::Moved(_)) => Err(FeedCreationError::RedirectLoop),
+        Err(FeedFetchError::NotFound) => Err(FeedCreationError::NotFound),
+        Err(err) => Err(FeedCreationError::OtherFetchError(err)),
+    }
+}
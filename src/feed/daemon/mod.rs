@@ -11,9 +11,11 @@ mod update;
 use anyhow::{Context, Result};
 use apply::apply_feed_update;
 use chrono::Utc;
+use constants::ICON_UPDATES_PER_TICK;
 use kube_leader_election::{LeaseLock, LeaseLockParams};
 use ormx::Insert;
 use rand::{distr::Alphanumeric, Rng};
+use reqwest::Url;
 use sqlx::PgPool;
 use std::{sync::Arc, time::Duration};
 use tokio::{
@@ -21,14 +23,17 @@ use tokio::{
     task::JoinHandle,
 };
 
-use create::get_feed_creation;
 pub use create::FeedCreationError;
+use create::{get_feed_creation, get_feed_preview};
 pub use update::FeedUpdate;
 
 use fetch::{build_reqwest_client, fetch_feed};
 use update::get_feed_update;
 
-use crate::sql::{Feed, InsertFeedItem};
+use crate::feed::icon::{build_icon_client, fetch_site_icon};
+use crate::feed::parser::feed::ParsedFeed;
+use crate::job::JobWorkers;
+use crate::sql::{Feed, FeedIcon, InsertFeedItem};
 
 fn generate_random_name(len: usize) -> String {
     rand::rng()
@@ -41,11 +46,15 @@ fn generate_random_name(len: usize) -> String {
 pub struct DaemonOptions {
     pub concurrent_updates: usize,
     pub lease_name: Option<String>,
+    /// Number of background jobs run concurrently. Jobs run on every replica, not only the
+    /// leader
+    pub job_workers: usize,
 }
 
 pub struct Daemon {
     task: JoinHandle<()>,
     cancel_tx: oneshot::Sender<()>,
+    workers: JobWorkers,
 }
 
 impl Daemon {
@@ -71,6 +80,8 @@ impl Daemon {
             None
         };
 
+        let workers = JobWorkers::start(pool.clone(), options.job_workers);
+
         // TODO: handle the task failing
         let task = tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(60));
@@ -111,12 +122,21 @@ impl Daemon {
                         if let Err(err) = Daemon::update_outdated_feeds(&pool, options.concurrent_updates).await {
                             tracing::error!("Error in update: {:?}", err);
                         }
+
+                        tracing::info!("Running feed icon update");
+                        if let Err(err) = Daemon::update_missing_icons(&pool, options.concurrent_updates).await {
+                            tracing::error!("Error in icon update: {:?}", err);
+                        }
                     }
                 }
             }
         });
 
-        Ok(Self { task, cancel_tx })
+        Ok(Self {
+            task,
+            cancel_tx,
+            workers,
+        })
     }
 
     /// Runs a single feed update with the provided concurrency limit by first fetching all
@@ -143,6 +163,41 @@ impl Daemon {
         Ok(())
     }
 
+    /// Fetches the icons of feeds which don't have one yet or haven't been checked in a week,
+    /// running the fetches concurrently with the provided concurrency limit. Icons are looked up
+    /// on the home page of the feed's site rather than the feed itself, which isn't HTML.
+    async fn update_missing_icons(
+        pool: &PgPool,
+        concurrent_updates: usize,
+    ) -> Result<(), anyhow::Error> {
+        let semaphore = Arc::new(Semaphore::new(concurrent_updates));
+        let client = build_icon_client();
+        let mut handles = Vec::new();
+
+        for (feed_id, link) in Feed::get_missing_icons(pool, ICON_UPDATES_PER_TICK).await? {
+            let permit = semaphore.clone().acquire_owned().await.unwrap();
+            let pool = pool.clone();
+            let client = client.clone();
+
+            handles.push(tokio::spawn(async move {
+                let icon = match Url::parse(&link).and_then(|url| url.join("/")) {
+                    Ok(site) => fetch_site_icon(&client, &site).await,
+                    Err(_) => None,
+                };
+                FeedIcon::upsert(&pool, feed_id, icon.as_ref()).await?;
+
+                drop(permit);
+                Ok::<(), anyhow::Error>(())
+            }));
+        }
+
+        for handle in handles {
+            handle.await??;
+        }
+
+        Ok(())
+    }
+
     /// Updates a single feed via the following steps:
     ///
     /// 1. Fetch the feed's url
@@ -208,18 +263,27 @@ impl Daemon {
         Ok(feed)
     }
 
+    /// Fetches and parses the feed without storing anything in the database
+    pub async fn preview_feed(link: &str) -> Result<ParsedFeed, FeedCreationError> {
+        let client = build_reqwest_client(true);
+        let status = fetch_feed(&client, link, None, None).await;
+
+        get_feed_preview(status).await
+    }
+
     pub async fn cancel(self) {
         // TODO: timeout and force cancel
         self.cancel_tx.send(()).unwrap();
         if let Err(err) = self.task.await {
             tracing::error!("Error while canceling feed daemon: {:?}", err);
         }
+        self.workers.cancel().await;
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::sql::{Feed, FeedItem, InsertFeed};
+    use crate::sql::{Feed, FeedIcon, FeedItem, InsertFeed};
     use crate::tests::{dates::*, sql::TempDB};
 
     use super::Daemon;
@@ -402,4 +466,47 @@ mod test {
         assert_eq!(items.len(), 1);
         assert_eq!(items[0].title, "A Brief History of Code Signing at Mozilla");
     }
+
+    #[tokio::test]
+    async fn test_update_missing_icons() {
+        let mut server = mockito::Server::new_async().await;
+        let _feed = server
+            .mock("GET", "/blog/feed.xml")
+            .with_header("Content-Type", "application/rss+xml")
+            .with_body_from_file("tests/feeds/hacker-news-rss.xml")
+            .create();
+        let _page = server
+            .mock("GET", "/")
+            .with_header("Content-Type", "text/html")
+            .with_body(r#"<html><head><link rel="icon" href="/icon.png"></head></html>"#)
+            .create();
+        let _icon = server
+            .mock("GET", "/icon.png")
+            .with_header("Content-Type", "image/png")
+            .with_body([137, 80, 78, 71])
+            .create();
+
+        let pool = TempDB::new().await;
+
+        let feed = InsertFeed {
+            link: format!("{}/blog/feed.xml", server.url()),
+            ..InsertFeed::from_mockito(&server, Utc::now())
+        }
+        .insert(&*pool)
+        .await
+        .unwrap();
+
+        Daemon::update_missing_icons(&pool, 1).await.unwrap();
+        let icon = FeedIcon::get_by_feed(&*pool, &feed.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(icon.content_type, Some("image/png".to_string()));
+        assert_eq!(icon.data, Some(vec![137, 80, 78, 71]));
+        assert!(icon.hash.is_some());
+
+        // Icons which were just fetched shouldn't be fetched again
+        let missing = Feed::get_missing_icons(&pool, 10).await.unwrap();
+        assert!(missing.is_empty());
+    }
 }
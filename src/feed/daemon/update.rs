@@ -22,6 +22,7 @@ pub struct FeedUpdate {
     pub title: Option<String>,
     pub description: Option<String>,
     pub icon: Option<String>,
+    pub language: Option<String>,
 
     pub skip_hours: Option<Vec<i32>>,
     pub skip_days_of_week: Option<Vec<i32>>,
@@ -66,6 +67,7 @@ pub async fn get_feed_update(fetch: Result<FeedFetch, FeedFetchError>, feed: &Fe
                 title: Some(parsed_feed.title),
                 description: Some(parsed_feed.description),
                 icon: parsed_feed.icon,
+                language: parsed_feed.language,
 
                 skip_hours: Some(parsed_feed.skip_hours),
                 skip_days_of_week: Some(parsed_feed.skip_days_of_week),
@@ -0,0 +1,290 @@
+use std::time::Duration;
+
+use reqwest::Url;
+use scraper::{Html, Selector};
+use tokio::task::JoinSet;
+
+use super::parser::parse_feed_from_response;
+use super::{FeedFormat, FeedInformation};
+
+pub mod resolvers;
+
+/// Paths commonly used by blogs and static site generators to serve their feed
+const WELL_KNOWN_FEED_PATHS: &[&str] =
+    &["/feed", "/rss.xml", "/atom.xml", "/index.xml", "/feed.json"];
+
+/// Names of the last path segment (ignoring the extension) which usually point to a feed
+const FEED_PATH_NAMES: &[&str] = &["feed", "rss", "atom"];
+
+/// Maximum number of `<a>` links to probe, since pages may link to many feeds (e.g. per category)
+const MAX_ANCHOR_CANDIDATES: usize = 10;
+
+/// Parses an HTML document and searches for feed links of all kind such as RSS, Atom, JSON etc.
+pub fn discover_feed_links(url: &Url, html: &str) -> Vec<Url> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("link[type]").unwrap();
+
+    document
+        .select(&selector)
+        .filter_map(|element| {
+            let link = element.value().attr("href")?;
+            match element.value().attr("type") {
+                Some("application/atom+xml")
+                | Some("application/rss+xml")
+                | Some("application/feed+json")
+                | Some("application/json") => url.join(link).ok(),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Parses an HTML document and searches for `<a>` links whose path ends in feed, rss or atom
+/// (e.g. `/blog/feed/`, `/rss.xml` or `/index.atom`)
+pub fn discover_anchor_links(url: &Url, html: &str) -> Vec<Url> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("a[href]").unwrap();
+
+    document
+        .select(&selector)
+        .filter_map(|element| url.join(element.value().attr("href")?).ok())
+        .filter(|link| matches!(link.scheme(), "http" | "https"))
+        .filter(is_feed_like_path)
+        .take(MAX_ANCHOR_CANDIDATES)
+        .collect()
+}
+
+/// Builds the list of well known feed locations for the site the URL belongs to
+pub fn well_known_feed_links(url: &Url) -> Vec<Url> {
+    WELL_KNOWN_FEED_PATHS
+        .iter()
+        .filter_map(|path| url.join(path).ok())
+        .collect()
+}
+
+fn is_feed_like_path(url: &Url) -> bool {
+    let segment = url
+        .path()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let (name, extension) = segment.split_once('.').unwrap_or((segment.as_str(), ""));
+
+    FEED_PATH_NAMES.contains(&name) || matches!(extension, "rss" | "atom")
+}
+
+/// Discovers all feeds available for a website by:
+///
+/// 1. Fetching the page and checking if it's a feed itself
+/// 2. Collecting candidates from platform resolvers (YouTube, Reddit etc.), `<link>` elements,
+///    `<a>` links and well known feed paths
+/// 3. Fetching and parsing every candidate, only keeping the ones that are valid feeds
+///
+/// The returned feeds are in the order they were discovered in and unique by their final URL.
+pub async fn discover_feeds(
+    client: &reqwest::Client,
+    url: &Url,
+) -> Result<Vec<FeedInformation>, reqwest::Error> {
+    let response = client.get(url.clone()).send().await?;
+    let final_url = response.url().clone(); // We may have redirected, so get the final URL
+    let is_feed = response
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .and_then(FeedFormat::from_content_type)
+        .is_some();
+
+    let mut candidates = vec![];
+    if is_feed {
+        candidates.push(final_url.clone());
+    } else {
+        let html_page = response.text().await?;
+        // Resolve using the requested URL, since platforms often redirect to consent or login pages
+        candidates.extend(resolvers::resolve_platform_feeds(url, &html_page));
+        candidates.extend(discover_feed_links(&final_url, &html_page));
+        candidates.extend(discover_anchor_links(&final_url, &html_page));
+    }
+    candidates.extend(well_known_feed_links(&final_url));
+
+    Ok(validate_feed_links(client, candidates).await)
+}
+
+/// Fetches and parses each of the links concurrently, returning information about the
+/// ones which are valid feeds
+pub async fn validate_feed_links(
+    client: &reqwest::Client,
+    links: Vec<Url>,
+) -> Vec<FeedInformation> {
+    let mut unique_links: Vec<Url> = Vec::with_capacity(links.len());
+    for link in links {
+        if !unique_links.contains(&link) {
+            unique_links.push(link);
+        }
+    }
+
+    let mut tasks = JoinSet::new();
+    for (idx, link) in unique_links.into_iter().enumerate() {
+        let client = client.clone();
+        tasks.spawn(async move { (idx, validate_feed_link(&client, link).await) });
+    }
+
+    let mut feeds = vec![];
+    while let Some(result) = tasks.join_next().await {
+        if let Ok((idx, Some(feed))) = result {
+            feeds.push((idx, feed));
+        }
+    }
+    feeds.sort_by_key(|(idx, _)| *idx);
+
+    let mut unique_feeds: Vec<FeedInformation> = Vec::with_capacity(feeds.len());
+    for (_, feed) in feeds {
+        if !unique_feeds.iter().any(|existing| existing.url == feed.url) {
+            unique_feeds.push(feed);
+        }
+    }
+    unique_feeds
+}
+
+async fn validate_feed_link(client: &reqwest::Client, link: Url) -> Option<FeedInformation> {
+    let response = client
+        .get(link)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+
+    let url = response.url().to_string();
+    let parsed_feed = parse_feed_from_response(response).await.ok()?;
+
+    Some(FeedInformation {
+        url,
+        format: parsed_feed.format,
+        title: parsed_feed.title,
+        item_count: parsed_feed.items.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_link_elements() {
+        let url = Url::parse("https://example.com/blog/").unwrap();
+        let html = r#"
+            <html>
+                <head>
+                    <link rel="alternate" type="application/rss+xml" href="/rss.xml">
+                    <link rel="alternate" type="application/atom+xml" href="atom.xml">
+                    <link rel="stylesheet" type="text/css" href="/style.css">
+                </head>
+            </html>
+        "#;
+
+        let links = discover_feed_links(&url, html);
+        assert_eq!(
+            links,
+            vec![
+                Url::parse("https://example.com/rss.xml").unwrap(),
+                Url::parse("https://example.com/blog/atom.xml").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_feed_like_anchors() {
+        let url = Url::parse("https://example.com/").unwrap();
+        let html = r#"
+            <html>
+                <body>
+                    <a href="/blog/feed/">Subscribe</a>
+                    <a href="/index.rss">RSS</a>
+                    <a href="https://example.com/releases.atom">Releases</a>
+                    <a href="/feedback">Feedback</a>
+                    <a href="/about">About</a>
+                    <a href="mailto:rss@example.com">Contact</a>
+                </body>
+            </html>
+        "#;
+
+        let links = discover_anchor_links(&url, html);
+        assert_eq!(
+            links,
+            vec![
+                Url::parse("https://example.com/blog/feed/").unwrap(),
+                Url::parse("https://example.com/index.rss").unwrap(),
+                Url::parse("https://example.com/releases.atom").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_well_known_links_from_site_root() {
+        let url = Url::parse("https://example.com/blog/post?id=1").unwrap();
+        let links = well_known_feed_links(&url);
+
+        assert_eq!(links.len(), WELL_KNOWN_FEED_PATHS.len());
+        assert_eq!(links[0].as_str(), "https://example.com/feed");
+        assert_eq!(links[4].as_str(), "https://example.com/feed.json");
+    }
+
+    #[tokio::test]
+    async fn only_returns_valid_feeds() {
+        let mut server = mockito::Server::new_async().await;
+        let html = r#"
+            <html>
+                <head><link rel="alternate" type="application/rss+xml" href="/rss"></head>
+                <body><a href="/blog/feed">Feed</a></body>
+            </html>
+        "#;
+        let _page = server
+            .mock("GET", "/")
+            .with_header("Content-Type", "text/html")
+            .with_body(html)
+            .create_async()
+            .await;
+        let _rss = server
+            .mock("GET", "/rss")
+            .with_header("Content-Type", "application/rss+xml")
+            .with_body_from_file("tests/feeds/hacker-news-rss.xml")
+            .create_async()
+            .await;
+        let _html_feed = server
+            .mock("GET", "/blog/feed")
+            .with_header("Content-Type", "text/html")
+            .with_body(html)
+            .create_async()
+            .await;
+        let _json = server
+            .mock("GET", "/feed.json")
+            .with_header("Content-Type", "application/json")
+            .with_body(
+                r#"{
+                    "version": "https://jsonfeed.org/version/1.1",
+                    "title": "JSON Feed",
+                    "feed_url": "https://example.org/feed.json",
+                    "items": []
+                }"#,
+            )
+            .create_async()
+            .await;
+
+        let url = Url::parse(&server.url()).unwrap();
+        let feeds = discover_feeds(&reqwest::Client::new(), &url).await.unwrap();
+
+        assert_eq!(feeds.len(), 2);
+        assert_eq!(feeds[0].url, format!("{}/rss", server.url()));
+        assert_eq!(feeds[0].format, FeedFormat::Rss);
+        assert_eq!(feeds[0].title, "Hacker News");
+        assert_eq!(feeds[0].item_count, 1);
+        assert_eq!(feeds[1].format, FeedFormat::Json);
+        assert_eq!(feeds[1].title, "JSON Feed");
+        assert_eq!(feeds[1].item_count, 0);
+    }
+}
@@ -0,0 +1,291 @@
+use std::sync::LazyLock;
+
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+static YOUTUBE_CHANNEL_ID_REGEX: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r#""(?:channelId|externalId)":"(UC[a-zA-Z0-9_-]{22})""#).unwrap()
+});
+
+/// Maps a page on a platform which doesn't advertise its feeds (or hides them behind
+/// consent walls and JavaScript) to the platform's native feed URLs
+pub trait FeedResolver: Sync {
+    /// Returns the feed URLs for the page, or nothing if the resolver doesn't handle the URL
+    fn resolve(&self, url: &Url, html: &str) -> Vec<Url>;
+}
+
+/// All resolvers which are tried during discovery, in order of priority
+pub static RESOLVERS: &[&dyn FeedResolver] = &[&YouTube, &Reddit, &GitHub, &Mastodon];
+
+/// Runs every registered resolver against the page, returning the feed URLs they produced
+pub fn resolve_platform_feeds(url: &Url, html: &str) -> Vec<Url> {
+    RESOLVERS
+        .iter()
+        .flat_map(|resolver| resolver.resolve(url, html))
+        .collect()
+}
+
+fn path_segments(url: &Url) -> Vec<&str> {
+    url.path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+fn has_host(url: &Url, hosts: &[&str]) -> bool {
+    url.host_str()
+        .map(|host| hosts.contains(&host.trim_start_matches("www.")))
+        .unwrap_or(false)
+}
+
+/// Resolves channels and playlists to `https://www.youtube.com/feeds/videos.xml`
+///
+/// Channels linked by handle (`/@name`), custom URL (`/c/name`) or legacy username (`/user/name`)
+/// don't include the channel ID in the URL, so it's looked up from the page instead
+pub struct YouTube;
+
+impl YouTube {
+    fn feed_url(key: &str, value: &str) -> Vec<Url> {
+        Url::parse_with_params("https://www.youtube.com/feeds/videos.xml", &[(key, value)])
+            .into_iter()
+            .collect()
+    }
+
+    fn channel_id_from_html(html: &str) -> Option<String> {
+        let document = Html::parse_document(html);
+
+        let meta =
+            Selector::parse(r#"meta[itemprop="channelId"], meta[itemprop="identifier"]"#).unwrap();
+        let from_meta = document
+            .select(&meta)
+            .filter_map(|element| element.value().attr("content"))
+            .find(|content| content.starts_with("UC"));
+
+        let canonical = Selector::parse(r#"link[rel="canonical"]"#).unwrap();
+        let from_canonical = document
+            .select(&canonical)
+            .filter_map(|element| element.value().attr("href"))
+            .filter_map(|href| href.split("/channel/").nth(1))
+            .map(|channel_id| channel_id.trim_end_matches('/'))
+            .find(|channel_id| channel_id.starts_with("UC"));
+
+        from_meta
+            .or(from_canonical)
+            .map(|channel_id| channel_id.to_string())
+            .or_else(|| {
+                YOUTUBE_CHANNEL_ID_REGEX
+                    .captures(html)
+                    .map(|captures| captures[1].to_string())
+            })
+    }
+}
+
+impl FeedResolver for YouTube {
+    fn resolve(&self, url: &Url, html: &str) -> Vec<Url> {
+        if !has_host(url, &["youtube.com", "m.youtube.com"]) {
+            return vec![];
+        }
+
+        match path_segments(url).as_slice() {
+            ["channel", channel_id, ..] => Self::feed_url("channel_id", channel_id),
+            ["playlist"] => url
+                .query_pairs()
+                .find(|(key, _)| key == "list")
+                .map(|(_, playlist_id)| Self::feed_url("playlist_id", &playlist_id))
+                .unwrap_or_default(),
+            [handle, ..] if handle.starts_with('@') => Self::channel_id_from_html(html)
+                .map(|channel_id| Self::feed_url("channel_id", &channel_id))
+                .unwrap_or_default(),
+            ["c" | "user", _, ..] => Self::channel_id_from_html(html)
+                .map(|channel_id| Self::feed_url("channel_id", &channel_id))
+                .unwrap_or_default(),
+            _ => vec![],
+        }
+    }
+}
+
+/// Resolves subreddits (`/r/name`) and users (`/u/name`, `/user/name`) by appending `/.rss`
+pub struct Reddit;
+
+impl FeedResolver for Reddit {
+    fn resolve(&self, url: &Url, _html: &str) -> Vec<Url> {
+        if !has_host(url, &["reddit.com", "old.reddit.com", "new.reddit.com"]) {
+            return vec![];
+        }
+
+        let path = match path_segments(url).as_slice() {
+            ["r", subreddit, ..] => format!("/r/{}/.rss", subreddit),
+            ["u" | "user", username, ..] => format!("/user/{}/.rss", username),
+            _ => return vec![],
+        };
+        Url::parse("https://www.reddit.com")
+            .and_then(|base| base.join(&path))
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Resolves repositories to their releases and commits Atom feeds, and users or organizations
+/// to their public activity feed
+pub struct GitHub;
+
+impl FeedResolver for GitHub {
+    fn resolve(&self, url: &Url, _html: &str) -> Vec<Url> {
+        if !has_host(url, &["github.com"]) {
+            return vec![];
+        }
+
+        let paths = match path_segments(url).as_slice() {
+            [owner] => vec![format!("/{}.atom", owner)],
+            [owner, repo, ..] => vec![
+                format!("/{}/{}/releases.atom", owner, repo),
+                format!("/{}/{}/commits.atom", owner, repo),
+            ],
+            _ => return vec![],
+        };
+        paths
+            .iter()
+            .filter_map(|path| url.join(path).ok())
+            .collect()
+    }
+}
+
+/// Resolves Mastodon profiles (`/@name` or `/users/name`) on any instance by appending `.rss`
+///
+/// Since Mastodon can be hosted on any domain, the page must also be served by Mastodon, which
+/// names itself in the `application-name` meta tag and mounts its web app on `#mastodon`
+pub struct Mastodon;
+
+impl Mastodon {
+    fn is_mastodon_page(html: &str) -> bool {
+        let document = Html::parse_document(html);
+
+        let application_name = Selector::parse(r#"meta[name="application-name"]"#).unwrap();
+        let named = document
+            .select(&application_name)
+            .filter_map(|element| element.value().attr("content"))
+            .any(|name| name.eq_ignore_ascii_case("mastodon"));
+        let app_holder = Selector::parse("#mastodon").unwrap();
+        named || document.select(&app_holder).next().is_some()
+    }
+}
+
+impl FeedResolver for Mastodon {
+    fn resolve(&self, url: &Url, html: &str) -> Vec<Url> {
+        if !Self::is_mastodon_page(html) {
+            return vec![];
+        }
+
+        let path = match path_segments(url).as_slice() {
+            [handle] if handle.starts_with('@') => format!("/{}.rss", handle),
+            ["users", username] => format!("/users/{}.rss", username),
+            _ => return vec![],
+        };
+        url.join(&path).into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(url: &str, html: &str) -> Vec<String> {
+        resolve_platform_feeds(&Url::parse(url).unwrap(), html)
+            .into_iter()
+            .map(|url| url.to_string())
+            .collect()
+    }
+
+    fn fixture(name: &str) -> String {
+        std::fs::read_to_string(format!("tests/pages/{}", name)).unwrap()
+    }
+
+    #[test]
+    fn youtube_channel_id_from_url() {
+        assert_eq!(
+            resolve(
+                "https://www.youtube.com/channel/UCsBjURrPoezykLs9EqgamOA/videos",
+                ""
+            ),
+            vec!["https://www.youtube.com/feeds/videos.xml?channel_id=UCsBjURrPoezykLs9EqgamOA"]
+        );
+    }
+
+    #[test]
+    fn youtube_handle_from_page() {
+        assert_eq!(
+            resolve(
+                "https://www.youtube.com/@Fireship",
+                &fixture("youtube-channel.html")
+            ),
+            vec!["https://www.youtube.com/feeds/videos.xml?channel_id=UCsBjURrPoezykLs9EqgamOA"]
+        );
+    }
+
+    #[test]
+    fn youtube_channel_id_from_initial_data() {
+        let html = r#"<script>var ytInitialData = {"metadata":{"channelMetadataRenderer":{"externalId":"UCsBjURrPoezykLs9EqgamOA"}}};</script>"#;
+        assert_eq!(
+            resolve("https://youtube.com/c/Fireship", html),
+            vec!["https://www.youtube.com/feeds/videos.xml?channel_id=UCsBjURrPoezykLs9EqgamOA"]
+        );
+    }
+
+    #[test]
+    fn youtube_playlist() {
+        assert_eq!(
+            resolve(
+                "https://www.youtube.com/playlist?list=PL0vfts4VzfNjQOM9VClyL5R0LeuTxlAR3",
+                ""
+            ),
+            vec!["https://www.youtube.com/feeds/videos.xml?playlist_id=PL0vfts4VzfNjQOM9VClyL5R0LeuTxlAR3"]
+        );
+    }
+
+    #[test]
+    fn reddit_subreddit_and_user() {
+        assert_eq!(
+            resolve("https://old.reddit.com/r/rust/top/?t=week", ""),
+            vec!["https://www.reddit.com/r/rust/.rss"]
+        );
+        assert_eq!(
+            resolve("https://www.reddit.com/u/spez", ""),
+            vec!["https://www.reddit.com/user/spez/.rss"]
+        );
+        assert!(resolve("https://www.reddit.com/settings", "").is_empty());
+    }
+
+    #[test]
+    fn github_repository_and_user() {
+        assert_eq!(
+            resolve("https://github.com/rust-lang/rust/tree/master", ""),
+            vec![
+                "https://github.com/rust-lang/rust/releases.atom",
+                "https://github.com/rust-lang/rust/commits.atom"
+            ]
+        );
+        assert_eq!(
+            resolve("https://github.com/rust-lang", ""),
+            vec!["https://github.com/rust-lang.atom"]
+        );
+    }
+
+    #[test]
+    fn mastodon_profile() {
+        let html = fixture("mastodon-profile.html");
+        assert_eq!(
+            resolve("https://hachyderm.io/@rust", &html),
+            vec!["https://hachyderm.io/@rust.rss"]
+        );
+        assert_eq!(
+            resolve("https://hachyderm.io/users/rust", &html),
+            vec!["https://hachyderm.io/users/rust.rss"]
+        );
+    }
+
+    #[test]
+    fn mastodon_requires_mastodon_page() {
+        assert!(resolve("https://example.com/@rust", "<html></html>").is_empty());
+        let blog = r#"<html><body><a href="https://hachyderm.io/@rust">Follow me on Mastodon</a></body></html>"#;
+        assert!(resolve("https://example.com/@rust", blog).is_empty());
+    }
+}
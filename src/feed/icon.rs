@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+use crate::constants::USER_AGENT;
+
+/// Icons larger than this are ignored, since they're only shown next to the feed's title
+const MAX_ICON_SIZE: usize = 1024 * 1024;
+
+/// Icon downloaded for a website
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchedIcon {
+    /// URL the icon was downloaded from
+    pub link: String,
+    /// MIME type of the icon (e.g. `image/png`)
+    pub content_type: String,
+    /// Raw bytes of the icon
+    pub data: Vec<u8>,
+}
+
+pub fn build_icon_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .redirect(reqwest::redirect::Policy::limited(10))
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap()
+}
+
+/// Parses an HTML document and returns the icons it advertises, with apple-touch-icons first
+/// since they're usually higher resolution than the regular favicon
+pub fn discover_icon_links(url: &Url, html: &str) -> Vec<Url> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("link[rel][href]").unwrap();
+
+    let mut touch_icons = vec![];
+    let mut icons = vec![];
+    for element in document.select(&selector) {
+        let rel = element.value().attr("rel").unwrap().to_lowercase();
+        let Some(link) = element
+            .value()
+            .attr("href")
+            .and_then(|href| url.join(href).ok())
+        else {
+            continue;
+        };
+
+        let mut kinds = rel.split_ascii_whitespace();
+        if kinds
+            .clone()
+            .any(|kind| kind.starts_with("apple-touch-icon"))
+        {
+            touch_icons.push(link);
+        } else if kinds.any(|kind| kind == "icon") {
+            icons.push(link);
+        }
+    }
+
+    touch_icons.extend(icons);
+    touch_icons
+}
+
+/// Returns the `/favicon.ico` of the website, which browsers fall back to when the page
+/// doesn't advertise any icons
+pub fn default_icon_link(url: &Url) -> Option<Url> {
+    url.join("/favicon.ico").ok()
+}
+
+/// Finds and downloads the icon of the website by:
+///
+/// 1. Fetching the page and collecting icons from `<link rel="icon">` and
+///    `<link rel="apple-touch-icon">` elements
+/// 2. Falling back to `/favicon.ico`
+/// 3. Downloading each candidate in order, returning the first one that is an image
+pub async fn fetch_site_icon(client: &reqwest::Client, url: &Url) -> Option<FetchedIcon> {
+    let mut candidates = vec![];
+    if let Ok(response) = client.get(url.clone()).send().await {
+        let final_url = response.url().clone();
+        if let Ok(html_page) = response.text().await {
+            candidates.extend(discover_icon_links(&final_url, &html_page));
+        }
+    }
+    candidates.extend(default_icon_link(url));
+
+    for candidate in candidates {
+        if let Some(icon) = fetch_icon(client, candidate).await {
+            return Some(icon);
+        }
+    }
+    None
+}
+
+async fn fetch_icon(client: &reqwest::Client, link: Url) -> Option<FetchedIcon> {
+    let response = client.get(link).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+
+    let link = response.url().to_string();
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_lowercase())?;
+    if !content_type.starts_with("image/") {
+        return None;
+    }
+
+    let data = response.bytes().await.ok()?;
+    if data.is_empty() || data.len() > MAX_ICON_SIZE {
+        return None;
+    }
+
+    Some(FetchedIcon {
+        link,
+        content_type,
+        data: data.to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_icon_links() {
+        let url = Url::parse("https://example.com/blog/").unwrap();
+        let html = r#"
+            <html>
+                <head>
+                    <link rel="shortcut icon" href="/favicon.png">
+                    <link rel="stylesheet" href="/style.css">
+                    <link rel="apple-touch-icon" sizes="180x180" href="touch.png">
+                </head>
+            </html>
+        "#;
+
+        let links = discover_icon_links(&url, html);
+        assert_eq!(
+            links,
+            vec![
+                Url::parse("https://example.com/blog/touch.png").unwrap(),
+                Url::parse("https://example.com/favicon.png").unwrap(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_favicon_ico() {
+        let mut server = mockito::Server::new_async().await;
+        let _page = server
+            .mock("GET", "/")
+            .with_header("Content-Type", "text/html")
+            .with_body(r#"<html><head><link rel="icon" href="/missing.png"></head></html>"#)
+            .create_async()
+            .await;
+        let _missing = server
+            .mock("GET", "/missing.png")
+            .with_status(404)
+            .create_async()
+            .await;
+        let _favicon = server
+            .mock("GET", "/favicon.ico")
+            .with_header("Content-Type", "image/x-icon")
+            .with_body([0, 0, 1, 0])
+            .create_async()
+            .await;
+
+        let url = Url::parse(&server.url()).unwrap();
+        let icon = fetch_site_icon(&build_icon_client(), &url).await.unwrap();
+        assert_eq!(icon.link, format!("{}/favicon.ico", server.url()));
+        assert_eq!(icon.content_type, "image/x-icon");
+        assert_eq!(icon.data, vec![0, 0, 1, 0]);
+    }
+}
@@ -39,6 +39,7 @@ pub struct JsonFeedItem {
     pub date_published: Option<DateTime<Utc>>,
     pub date_modified: Option<DateTime<Utc>>,
     pub author: Option<Author>,
+    pub authors: Option<Vec<Author>>,
     pub tags: Option<Vec<String>>,
     pub attachments: Option<Vec<Attachment>>,
 }
@@ -56,6 +57,7 @@ pub struct JsonFeed {
     pub icon: Option<String>,
     pub favicon: Option<String>,
     pub author: Option<Author>,
+    pub language: Option<String>,
     pub expired: Option<bool>,
     pub hubs: Option<Vec<Hub>>,
 }
@@ -1,7 +1,10 @@
 pub mod daemon;
 pub mod discover;
+pub mod icon;
 pub mod json;
+pub mod opml;
 pub mod parser;
+pub mod publish;
 
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -15,4 +18,8 @@ pub struct FeedInformation {
     pub url: String,
     /// Format of the feed (RSS, Atom, or JSON)
     pub format: FeedFormat,
+    /// Title of the feed
+    pub title: String,
+    /// Number of items currently in the feed
+    pub item_count: usize,
 }
@@ -0,0 +1,249 @@
+use quick_xml::encoding::Decoder;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+
+#[derive(Debug, thiserror::Error)]
+pub enum OpmlError {
+    #[error("invalid OPML: {0}")]
+    Xml(#[from] quick_xml::Error),
+    #[error("invalid OPML: missing <opml> element")]
+    NotOpml,
+}
+
+/// An outline of an OPML document, which is either a feed when it has a link, or a folder of
+/// other outlines
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Outline {
+    pub title: String,
+    /// URL of the feed
+    pub xml_url: Option<String>,
+    pub children: Vec<Outline>,
+}
+
+impl Outline {
+    pub fn feed(title: impl Into<String>, xml_url: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            xml_url: Some(xml_url.into()),
+            children: vec![],
+        }
+    }
+
+    pub fn folder(title: impl Into<String>, children: Vec<Outline>) -> Self {
+        Self {
+            title: title.into(),
+            xml_url: None,
+            children,
+        }
+    }
+
+    /// Returns the feeds of the outline and of all its descendants
+    pub fn feeds(&self) -> Vec<&Outline> {
+        let mut feeds = vec![];
+        if self.xml_url.is_some() {
+            feeds.push(self);
+        }
+        for child in &self.children {
+            feeds.extend(child.feeds());
+        }
+        feeds
+    }
+}
+
+/// OPML document listing subscriptions, as exported and imported by feed readers
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Opml {
+    pub title: Option<String>,
+    pub outlines: Vec<Outline>,
+}
+
+impl Opml {
+    pub fn parse(input: &str) -> Result<Self, OpmlError> {
+        let mut reader = Reader::from_str(input);
+        reader.config_mut().trim_text(true);
+
+        let mut opml = None;
+        let mut in_title = false;
+        // Outlines which have been opened but not closed yet, innermost last
+        let mut parents: Vec<Outline> = vec![];
+        loop {
+            match reader.read_event()? {
+                Event::Start(element) => match element.local_name().as_ref() {
+                    b"opml" => opml = Some(Opml::default()),
+                    b"title" => in_title = parents.is_empty(),
+                    b"outline" => parents.push(parse_outline(&element, reader.decoder())?),
+                    _ => {}
+                },
+                Event::Empty(element) if element.local_name().as_ref() == b"outline" => {
+                    let outline = parse_outline(&element, reader.decoder())?;
+                    push_outline(&mut opml, &mut parents, outline);
+                }
+                Event::Text(text) if in_title => {
+                    if let Some(opml) = opml.as_mut() {
+                        opml.title = Some(text.unescape()?.into_owned());
+                    }
+                }
+                Event::End(element) => match element.local_name().as_ref() {
+                    b"title" => in_title = false,
+                    b"outline" => {
+                        if let Some(outline) = parents.pop() {
+                            push_outline(&mut opml, &mut parents, outline);
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        opml.ok_or(OpmlError::NotOpml)
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+        // Writing to a Vec can't fail
+        let _ = write_opml(&mut writer, self);
+        String::from_utf8(writer.into_inner()).unwrap_or_default()
+    }
+}
+
+fn parse_outline(element: &BytesStart, decoder: Decoder) -> Result<Outline, OpmlError> {
+    let mut text = None;
+    let mut title = None;
+    let mut xml_url = None;
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        let value = attribute.decode_and_unescape_value(decoder)?.into_owned();
+        match attribute.key.as_ref().to_ascii_lowercase().as_slice() {
+            b"text" => text = Some(value),
+            b"title" => title = Some(value),
+            b"xmlurl" => xml_url = Some(value).filter(|url| !url.is_empty()),
+            _ => {}
+        }
+    }
+
+    Ok(Outline {
+        // Readers disagree on which of the two is the name shown to users
+        title: title.or(text).unwrap_or_default(),
+        xml_url,
+        children: vec![],
+    })
+}
+
+fn push_outline(opml: &mut Option<Opml>, parents: &mut [Outline], outline: Outline) {
+    match parents.last_mut() {
+        Some(parent) => parent.children.push(outline),
+        None => {
+            if let Some(opml) = opml.as_mut() {
+                opml.outlines.push(outline);
+            }
+        }
+    }
+}
+
+fn write_opml<W: std::io::Write>(writer: &mut Writer<W>, opml: &Opml) -> std::io::Result<()> {
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer.write_event(Event::Start(
+        BytesStart::new("opml").with_attributes([("version", "2.0")]),
+    ))?;
+
+    writer.write_event(Event::Start(BytesStart::new("head")))?;
+    if let Some(title) = &opml.title {
+        writer.write_event(Event::Start(BytesStart::new("title")))?;
+        writer.write_event(Event::Text(BytesText::new(title)))?;
+        writer.write_event(Event::End(BytesEnd::new("title")))?;
+    }
+    writer.write_event(Event::End(BytesEnd::new("head")))?;
+
+    writer.write_event(Event::Start(BytesStart::new("body")))?;
+    for outline in &opml.outlines {
+        write_outline(writer, outline)?;
+    }
+    writer.write_event(Event::End(BytesEnd::new("body")))?;
+    writer.write_event(Event::End(BytesEnd::new("opml")))?;
+    Ok(())
+}
+
+fn write_outline<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    outline: &Outline,
+) -> std::io::Result<()> {
+    let mut element = BytesStart::new("outline")
+        .with_attributes([("text", outline.title.as_str()), ("title", &outline.title)]);
+    if let Some(xml_url) = &outline.xml_url {
+        element.extend_attributes([("type", "rss"), ("xmlUrl", xml_url)]);
+    }
+
+    if outline.children.is_empty() {
+        return writer.write_event(Event::Empty(element));
+    }
+    writer.write_event(Event::Start(element))?;
+    for child in &outline.children {
+        write_outline(writer, child)?;
+    }
+    writer.write_event(Event::End(BytesEnd::new("outline")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_outlines() {
+        let opml = Opml::parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <opml version="1.0">
+              <head><title>Subscriptions</title></head>
+              <body>
+                <outline text="Tech" title="Tech">
+                  <outline type="rss" text="Hacker News" xmlUrl="https://news.ycombinator.com/rss"/>
+                  <outline text="Rust">
+                    <outline type="rss" text="This Week in Rust" xmlUrl="https://this-week-in-rust.org/rss.xml"></outline>
+                  </outline>
+                </outline>
+                <outline type="rss" text="Tom &amp; Jerry" xmlUrl="https://example.com/feed?a=1&amp;b=2"/>
+              </body>
+            </opml>"#,
+        )
+        .unwrap();
+
+        assert_eq!(opml.title.as_deref(), Some("Subscriptions"));
+        assert_eq!(
+            opml.outlines,
+            vec![
+                Outline::folder(
+                    "Tech",
+                    vec![
+                        Outline::feed("Hacker News", "https://news.ycombinator.com/rss"),
+                        Outline::folder(
+                            "Rust",
+                            vec![Outline::feed(
+                                "This Week in Rust",
+                                "https://this-week-in-rust.org/rss.xml"
+                            )]
+                        ),
+                    ]
+                ),
+                Outline::feed("Tom & Jerry", "https://example.com/feed?a=1&b=2"),
+            ]
+        );
+        assert_eq!(opml.outlines[0].feeds().len(), 2);
+    }
+
+    #[test]
+    fn round_trips() {
+        let opml = Opml {
+            title: Some("Bind".to_string()),
+            outlines: vec![
+                Outline::folder("News", vec![Outline::feed("A & B", "https://a.com/feed")]),
+                Outline::feed("C", "https://c.com/feed?x=1&y=2"),
+            ],
+        };
+        assert_eq!(Opml::parse(&opml.to_xml()).unwrap(), opml);
+
+        assert!(matches!(
+            Opml::parse("<html></html>"),
+            Err(OpmlError::NotOpml)
+        ));
+    }
+}
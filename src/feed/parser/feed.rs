@@ -2,7 +2,11 @@ use chrono::{DateTime, Utc};
 
 use crate::{feed::json::JsonFeed, sql::FeedFormat};
 
-use super::{feed_item::ParsedFeedItem, utils::domain_from_link, ParsedFeedCreationError};
+use super::{
+    feed_item::ParsedFeedItem,
+    utils::{domain_from_link, language_code},
+    ParsedFeedCreationError,
+};
 
 #[derive(Debug, Clone)]
 pub struct ParsedFeed {
@@ -12,6 +16,7 @@ pub struct ParsedFeed {
     pub title: String,
     pub description: String,
     pub icon: Option<String>,
+    pub language: Option<String>,
 
     pub skip_hours: Vec<i32>,
     pub skip_days_of_week: Vec<i32>,
@@ -61,6 +66,7 @@ impl TryFrom<rss::Channel> for ParsedFeed {
             title: value.title,
             description: value.description,
             icon: value.image.as_ref().map(|image| image.url.clone()),
+            language: value.language.as_deref().and_then(language_code),
             skip_hours,
             skip_days_of_week,
             updated_at: value
@@ -91,6 +97,7 @@ impl TryFrom<atom_syndication::Feed> for ParsedFeed {
                 .map(|subtitle| subtitle.value)
                 .unwrap_or_default(),
             icon: value.icon,
+            language: value.lang.as_deref().and_then(language_code),
             skip_hours: Vec::new(),
             skip_days_of_week: Vec::new(),
             updated_at: Some(value.updated.with_timezone(&Utc)),
@@ -120,6 +127,7 @@ impl TryFrom<JsonFeed> for ParsedFeed {
             title: value.title,
             description: value.description.unwrap_or_default(),
             icon: value.icon,
+            language: value.language.as_deref().and_then(language_code),
             skip_hours: Vec::new(),
             skip_days_of_week: Vec::new(),
             updated_at: None,
@@ -10,6 +10,7 @@ pub struct ParsedFeedItem {
     pub link: Option<String>,
     pub title: String,
     pub description: Option<String>,
+    pub author: Option<String>,
     pub enclosure: Option<FeedItemEnclosure>,
     pub content: Option<String>,
     pub categories: Vec<String>,
@@ -44,6 +45,18 @@ impl TryFrom<rss::Item> for ParsedFeedItem {
             mime_type: enclosure.mime_type.clone(),
         });
 
+        let author = value.author.clone().or_else(|| {
+            value
+                .dublin_core_ext
+                .as_ref()
+                .and_then(|dublin_core| dublin_core.creators.first().cloned())
+        });
+        let categories = value
+            .categories
+            .iter()
+            .map(|category| category.name.clone())
+            .collect();
+
         let published_at = match value.pub_date {
             Some(pub_date) => Some(
                 DateTime::parse_from_rfc2822(&pub_date)
@@ -58,9 +71,10 @@ impl TryFrom<rss::Item> for ParsedFeedItem {
             title,
             link: value.link,
             description: value.description,
+            author,
             enclosure,
             content: value.content.map(|content| content.to_string()),
-            categories: Vec::new(),
+            categories,
             comments_link: value.comments.map(|comments| comments.to_string()),
             published_at,
         })
@@ -91,6 +105,13 @@ impl TryFrom<atom_syndication::Entry> for ParsedFeedItem {
             .find(|link| link.rel == "comments")
             .map(|link| link.href.clone());
 
+        let author = value.authors.first().map(|author| author.name.clone());
+        let categories = value
+            .categories
+            .iter()
+            .map(|category| category.label.clone().unwrap_or(category.term.clone()))
+            .collect();
+
         // atom content can either have a value or an src attribute which is a link to the content
         let content = value
             .content
@@ -101,11 +122,12 @@ impl TryFrom<atom_syndication::Entry> for ParsedFeedItem {
             title: value.title.value,
             link: Some(value.id),
             description: value.summary.map(|summary| summary.value),
+            author,
             enclosure,
             comments_link,
             published_at: value.published.map(|published| published.into()),
             content,
-            categories: Vec::new(),
+            categories,
         })
     }
 }
@@ -118,6 +140,12 @@ impl TryFrom<JsonFeedItem> for ParsedFeedItem {
             .title
             .or(content.clone())
             .ok_or(ParsedFeedCreationError::JsonFeedParsingError)?;
+        // JSON Feed 1.1 replaced author with authors
+        let author = value
+            .authors
+            .and_then(|authors| authors.into_iter().next())
+            .or(value.author)
+            .and_then(|author| author.name);
         let enclosure = value.attachments.and_then(|attachments| {
             attachments.first().map(|attachment| FeedItemEnclosure {
                 url: attachment.url.clone(),
@@ -131,6 +159,7 @@ impl TryFrom<JsonFeedItem> for ParsedFeedItem {
             title,
             link: value.url,
             description: value.summary,
+            author,
             enclosure,
             content,
             categories: value.tags.unwrap_or_default(),
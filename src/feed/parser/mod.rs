@@ -270,4 +270,33 @@ mod tests {
             Some("<p>Hello, world!</p>".to_string())
         );
     }
+
+    #[test]
+    fn from_rss_feed_with_metadata() {
+        let feed = r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+                <channel>
+                    <title>Feed with metadata</title>
+                    <link>https://example.com/feed</link>
+                    <description>Feed with metadata description</description>
+                    <language>en-us</language>
+                    <item>
+                        <title>Item 1</title>
+                        <link>https://example.com/item1</link>
+                        <dc:creator>Jane Doe</dc:creator>
+                        <category>Rust</category>
+                        <category>Programming</category>
+                    </item>
+                </channel>
+            </rss>
+        "#;
+
+        let channel = Channel::read_from(feed.as_bytes()).unwrap();
+        let parsed_feed = ParsedFeed::try_from(channel).unwrap();
+
+        assert_eq!(parsed_feed.language, Some("en".to_string()));
+        assert_eq!(parsed_feed.items[0].author, Some("Jane Doe".to_string()));
+        assert_eq!(parsed_feed.items[0].categories, vec!["Rust", "Programming"]);
+    }
 }
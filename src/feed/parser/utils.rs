@@ -5,3 +5,18 @@ pub fn domain_from_link(link: &str) -> Option<String> {
         .ok()
         .and_then(|url| url.domain().map(|domain| domain.to_string()))
 }
+
+/// Converts a language tag such as `en-US` to its ISO 639-1 code (`en`)
+pub fn language_code(language: &str) -> Option<String> {
+    let code = language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    if code.len() == 2 && code.chars().all(|c| c.is_ascii_alphabetic()) {
+        Some(code)
+    } else {
+        None
+    }
+}
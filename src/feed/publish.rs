@@ -0,0 +1,198 @@
+use chrono::{DateTime, Utc};
+
+use super::json::{Attachment, Author, JsonFeed, JsonFeedItem};
+use crate::sql::FeedItem;
+
+/// Version of the JSON Feed specification published feeds follow
+const JSON_FEED_VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+/// A feed built from items already stored in the database, such as the timeline of an index,
+/// which can be written in any of the formats feeds are parsed from
+#[derive(Debug, Clone)]
+pub struct PublishedFeed {
+    pub title: String,
+    pub description: Option<String>,
+    /// Page of the feed on the website
+    pub link: String,
+    /// When the feed or any of its items last changed
+    pub updated_at: DateTime<Utc>,
+    pub items: Vec<FeedItem>,
+}
+
+impl PublishedFeed {
+    pub fn to_rss(&self) -> String {
+        let items = self
+            .items
+            .iter()
+            .map(|item| rss::Item {
+                title: Some(item.title.clone()),
+                link: item.link.clone(),
+                description: item.description.clone(),
+                author: item.author.clone(),
+                categories: item
+                    .categories
+                    .iter()
+                    .map(|category| rss::Category {
+                        name: category.clone(),
+                        domain: None,
+                    })
+                    .collect(),
+                comments: item.comments_link.clone(),
+                enclosure: item.enclosure.as_ref().map(|enclosure| rss::Enclosure {
+                    url: enclosure.url.clone(),
+                    length: enclosure.length.to_string(),
+                    mime_type: enclosure.mime_type.clone(),
+                }),
+                guid: Some(rss::Guid {
+                    value: item.guid.clone(),
+                    permalink: false,
+                }),
+                pub_date: Some(published_at(item).to_rfc2822()),
+                content: item.content.clone(),
+                ..Default::default()
+            })
+            .collect();
+
+        rss::Channel {
+            title: self.title.clone(),
+            link: self.link.clone(),
+            description: self.description.clone().unwrap_or_default(),
+            last_build_date: Some(self.updated_at.to_rfc2822()),
+            items,
+            ..Default::default()
+        }
+        .to_string()
+    }
+
+    pub fn to_atom(&self) -> String {
+        let entries = self
+            .items
+            .iter()
+            .map(|item| atom_syndication::Entry {
+                title: atom_syndication::Text::plain(item.title.clone()),
+                id: item.guid.clone(),
+                updated: item.updated_at.fixed_offset(),
+                published: Some(published_at(item).fixed_offset()),
+                authors: item
+                    .author
+                    .iter()
+                    .map(|author| atom_syndication::Person {
+                        name: author.clone(),
+                        ..Default::default()
+                    })
+                    .collect(),
+                categories: item
+                    .categories
+                    .iter()
+                    .map(|category| atom_syndication::Category {
+                        term: category.clone(),
+                        ..Default::default()
+                    })
+                    .collect(),
+                links: item
+                    .link
+                    .iter()
+                    .map(|link| atom_syndication::Link {
+                        href: link.clone(),
+                        ..Default::default()
+                    })
+                    .chain(
+                        item.enclosure
+                            .as_ref()
+                            .map(|enclosure| atom_syndication::Link {
+                                href: enclosure.url.clone(),
+                                rel: "enclosure".to_string(),
+                                mime_type: Some(enclosure.mime_type.clone()),
+                                length: Some(enclosure.length.to_string()),
+                                ..Default::default()
+                            }),
+                    )
+                    .collect(),
+                summary: item.description.clone().map(atom_syndication::Text::html),
+                content: item
+                    .content
+                    .clone()
+                    .map(|content| atom_syndication::Content {
+                        value: Some(content),
+                        content_type: Some("html".to_string()),
+                        ..Default::default()
+                    }),
+                ..Default::default()
+            })
+            .collect();
+
+        atom_syndication::Feed {
+            title: atom_syndication::Text::plain(self.title.clone()),
+            id: self.link.clone(),
+            updated: self.updated_at.fixed_offset(),
+            subtitle: self.description.clone().map(atom_syndication::Text::plain),
+            links: vec![atom_syndication::Link {
+                href: self.link.clone(),
+                ..Default::default()
+            }],
+            entries,
+            ..Default::default()
+        }
+        .to_string()
+    }
+
+    pub fn to_json(&self) -> JsonFeed {
+        let items = self
+            .items
+            .iter()
+            .map(|item| JsonFeedItem {
+                id: item.guid.clone(),
+                url: item.link.clone(),
+                external_url: None,
+                title: Some(item.title.clone()),
+                content_text: None,
+                content_html: item.content.clone().or(item.description.clone()),
+                summary: item.description.clone(),
+                image: None,
+                banner_image: None,
+                date_published: Some(published_at(item)),
+                date_modified: Some(item.updated_at),
+                author: None,
+                authors: item.author.clone().map(|name| {
+                    vec![Author {
+                        name: Some(name),
+                        url: None,
+                        avatar: None,
+                    }]
+                }),
+                tags: Some(item.categories.clone()).filter(|tags| !tags.is_empty()),
+                attachments: item.enclosure.as_ref().map(|enclosure| {
+                    vec![Attachment {
+                        url: enclosure.url.clone(),
+                        mime_type: enclosure.mime_type.clone(),
+                        title: None,
+                        size_in_bytes: u64::try_from(enclosure.length).ok(),
+                        duration_in_seconds: None,
+                    }]
+                }),
+            })
+            .collect();
+
+        JsonFeed {
+            version: JSON_FEED_VERSION.to_string(),
+            title: self.title.clone(),
+            items,
+            home_page_url: Some(self.link.clone()),
+            feed_url: None,
+            description: self.description.clone(),
+            user_comment: None,
+            next_url: None,
+            icon: None,
+            favicon: None,
+            author: None,
+            language: None,
+            expired: None,
+            hubs: None,
+        }
+    }
+}
+
+/// When the item was published, falling back to when it was added for feeds without dates
+fn published_at(item: &FeedItem) -> DateTime<Utc> {
+    item.published_at.unwrap_or(item.created_at)
+}
@@ -35,10 +35,12 @@ pub struct Origins {
     pub ios: Url,
 }
 
-#[derive(Deserialize, Debug, ToSchema, IntoParams)]
+#[derive(Deserialize, Debug, Validate, ToSchema, IntoParams)]
 pub struct Pagination {
+    #[validate(range(min = 1))]
     pub page: i64,
     #[serde(default = "default_limit")]
+    #[validate(range(min = 1, max = 100))]
     pub limit: i64,
 }
 
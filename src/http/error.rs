@@ -6,7 +6,12 @@ use axum::{
 use lettre::transport::smtp::Error as SmtpError;
 use serde_json::json;
 
-use crate::{feed::daemon::FeedCreationError, scraper::WebParserError};
+use crate::{
+    feed::{daemon::FeedCreationError, opml::OpmlError},
+    query::QueryError,
+    scraper::WebParserError,
+    sql::PaginationError,
+};
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -49,12 +54,36 @@ pub enum Error {
 
     #[error(transparent)]
     WebParserError(#[from] WebParserError),
+
+    #[error(transparent)]
+    InvalidQuery(#[from] QueryError),
+
+    #[error(transparent)]
+    InvalidPagination(#[from] PaginationError),
+
+    #[error(transparent)]
+    InvalidOpml(#[from] OpmlError),
+}
+
+impl Error {
+    /// Maps a violation of a unique constraint, like a slug taken in the meantime, to a
+    /// conflict with the message
+    pub fn conflict_on_unique(message: String) -> impl FnOnce(sqlx::Error) -> Error {
+        move |err| match &err {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                Error::Conflict(message)
+            }
+            _ => Error::DatabaseError(err),
+        }
+    }
 }
 
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
         let (status, message) = match self {
-            Error::ValidationError(_) => (http::StatusCode::BAD_REQUEST, format!("{}", self)),
+            Error::ValidationError(_) | Error::InvalidPagination(_) | Error::InvalidOpml(_) => {
+                (http::StatusCode::BAD_REQUEST, format!("{}", self))
+            }
             Error::Forbidden(msg) | Error::BadRequest(msg) | Error::Conflict(msg) => {
                 (http::StatusCode::FORBIDDEN, msg)
             }
@@ -63,6 +92,11 @@ impl IntoResponse for Error {
             Error::NotOwner => (http::StatusCode::FORBIDDEN, format!("{}", self)),
             Error::LoginFailed => (http::StatusCode::UNAUTHORIZED, format!("{}", self)),
 
+            // Includes where the error is so clients can point at it
+            Error::InvalidQuery(err) => {
+                return (http::StatusCode::BAD_REQUEST, Json(err)).into_response();
+            }
+
             Error::WebParserError(_) => (
                 http::StatusCode::INTERNAL_SERVER_ERROR,
                 "Could not parse provided page".to_string(),
@@ -2,7 +2,7 @@ use bind_macros::IntoRequest;
 use reqwest::Url;
 use utoipa::ToSchema;
 
-use crate::feed::{discover::discover_feed_links, FeedInformation};
+use crate::feed::{discover, FeedInformation};
 use crate::http::common::*;
 
 /// Request to discover feeds from a website URL
@@ -15,6 +15,10 @@ pub struct DiscoverFeedsRequest {
 }
 
 /// Discover feeds from a website URL
+///
+/// Looks for feeds advertised via `<link>` elements, `<a>` links whose path ends in
+/// feed/rss/atom and well known paths such as `/feed` or `/atom.xml`. Each candidate is
+/// fetched and parsed, so only valid feeds are returned, along with their title and item count.
 #[utoipa::path(
     post,
     path = "/discover",
@@ -39,11 +43,9 @@ pub async fn discover_feeds(
 
     let url = Url::parse(&body.link).map_err(|_| Error::BadRequest("Invalid URL".to_string()))?;
 
-    let response = state.reqwest_client.get(url.clone()).send().await?;
-    let final_url = response.url().clone(); // We may have redirected, so get the final URL
-    let html_page = response.text().await?;
-
-    Ok(Json(discover_feed_links(&final_url, &html_page)))
+    Ok(Json(
+        discover::discover_feeds(&state.reqwest_client, &url).await?,
+    ))
 }
 
 #[cfg(test)]
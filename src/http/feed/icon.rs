@@ -0,0 +1,140 @@
+use axum::http::{header, HeaderMap, HeaderValue};
+
+use crate::http::common::*;
+use crate::sql::FeedIcon;
+
+/// How long clients may cache an icon before revalidating it
+const ICON_MAX_AGE: &str = "public, max-age=86400";
+
+/// Get the icon of a feed
+///
+/// Returns the icon of the feed's website, as discovered by the feed daemon from
+/// `<link rel="icon">`, `<link rel="apple-touch-icon">` or `/favicon.ico`. The response
+/// is the raw image with its original content type. Responses include an `ETag` so clients
+/// can revalidate with `If-None-Match` and receive 304 Not Modified if the icon hasn't changed.
+///
+/// This route doesn't require authentication, so it can be used directly as an image source.
+#[utoipa::path(
+    get,
+    path = "/{id}/icon",
+    tag = "feed",
+    params(
+        ("id" = i32, Path, description = "Feed ID")
+    ),
+    responses(
+        (status = 200, description = "Icon of the feed", content_type = "image/*"),
+        (status = 304, description = "Icon hasn't changed since the provided ETag"),
+        (status = 404, description = "Feed has no icon"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_feed_icon(
+    State(state): State<ApiContext>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+    let not_found = || Error::NotFound("Feed has no icon".to_string());
+    let icon = FeedIcon::get_by_feed(&state.pool, &id)
+        .await?
+        .ok_or_else(not_found)?;
+    let (Some(content_type), Some(data), Some(hash)) = (icon.content_type, icon.data, icon.hash)
+    else {
+        return Err(not_found());
+    };
+
+    let etag = format!("\"{}\"", hash);
+    let cache_headers = [
+        (
+            header::ETAG,
+            HeaderValue::from_str(&etag).map_err(anyhow::Error::from)?,
+        ),
+        (
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(ICON_MAX_AGE),
+        ),
+    ];
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.split(',').any(|tag| tag.trim() == etag))
+        .unwrap_or(false);
+    if not_modified {
+        return Ok((http::StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    let content_type = HeaderValue::from_str(&content_type).map_err(anyhow::Error::from)?;
+    Ok((cache_headers, [(header::CONTENT_TYPE, content_type)], data).into_response())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tests::TestContext;
+
+    use super::*;
+
+    async fn insert_icon(ctx: &TestContext) -> (i32, String) {
+        let feed = sqlx::query_scalar!(
+            r#"
+            INSERT INTO feed (format, link, title, description)
+            VALUES ('rss', 'https://example.com/feed', 'Example', '')
+            RETURNING id
+            "#
+        )
+        .fetch_one(&ctx.pool)
+        .await
+        .unwrap();
+
+        let icon = crate::feed::icon::FetchedIcon {
+            link: "https://example.com/favicon.ico".to_string(),
+            content_type: "image/x-icon".to_string(),
+            data: vec![0, 0, 1, 0],
+        };
+        FeedIcon::upsert(&ctx.pool, feed, Some(&icon))
+            .await
+            .unwrap();
+        let hash = FeedIcon::get_by_feed(&ctx.pool, &feed)
+            .await
+            .unwrap()
+            .unwrap()
+            .hash
+            .unwrap();
+
+        (feed, hash)
+    }
+
+    #[tokio::test]
+    async fn serves_icon_with_cache_headers() {
+        let ctx = TestContext::new().await;
+        let (feed, hash) = insert_icon(&ctx).await;
+
+        let request = http::Request::builder()
+            .uri(format!("/feed/{}/icon", feed))
+            .body(String::new())
+            .unwrap();
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/x-icon");
+        assert_eq!(response.headers()[header::ETAG], format!("\"{}\"", hash));
+
+        let request = http::Request::builder()
+            .uri(format!("/feed/{}/icon", feed))
+            .header(header::IF_NONE_MATCH, format!("\"{}\"", hash))
+            .body(String::new())
+            .unwrap();
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 304);
+    }
+
+    #[tokio::test]
+    async fn missing_icon_is_not_found() {
+        let ctx = TestContext::new().await;
+
+        let request = http::Request::builder()
+            .uri("/feed/1/icon")
+            .body(String::new())
+            .unwrap();
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 404);
+    }
+}
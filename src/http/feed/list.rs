@@ -1,11 +1,10 @@
 use crate::http::common::*;
-use crate::sql::Feed;
+use crate::sql::{Feed, FeedFormat, FeedStatus};
 
-/// List all available feeds
+/// List subscribed feeds
 ///
-/// Retrieves a list of all RSS/Atom feeds available in the system. This endpoint requires
-/// authentication and returns feeds that the system is aggregating. Users can subscribe to
-/// these feeds by adding them to their personal indexes.
+/// Retrieves the RSS/Atom feeds the user is subscribed to, ordered by title. To subscribe to
+/// a feed or customize how it is shown, use the subscription endpoints.
 ///
 /// Each feed contains metadata such as the title, description, URL, and update frequency.
 /// The actual feed content is accessed through the index endpoints.
@@ -14,7 +13,7 @@ use crate::sql::Feed;
     path = "/",
     tag = "feed",
     responses(
-        (status = 200, description = "List of all feeds", body = Vec<Feed>),
+        (status = 200, description = "List of the feeds the user is subscribed to", body = Vec<Feed>),
         (status = 401, description = "Unauthorized - Valid JWT token required"),
         (status = 500, description = "Internal server error")
     ),
@@ -22,6 +21,42 @@ use crate::sql::Feed;
         ("BearerToken" = [])
     )
 )]
-pub async fn list_feeds(_: AuthUser, State(state): State<ApiContext>) -> Result<Json<Vec<Feed>>> {
-    Ok(Json(Feed::all(&state.pool).await?))
+pub async fn list_feeds(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+) -> Result<Json<Vec<Feed>>> {
+    let feeds = sqlx::query_as!(
+        Feed,
+        r#"
+        SELECT
+            feed.id,
+            feed.status AS "status: FeedStatus",
+            feed.format AS "format: FeedFormat",
+            feed.link,
+            feed.domain,
+
+            feed.title,
+            feed.description,
+            feed.icon,
+            feed.language,
+
+            feed.skip_hours,
+            feed.skip_days_of_week,
+            feed.ttl_in_minutes,
+            feed.etag,
+
+            feed.created_at,
+            feed.updated_at,
+            feed.fetched_at,
+            feed.successful_fetch_at,
+            feed.next_fetch_at
+        FROM feed JOIN user_feed ON user_feed.feed = feed.id
+        WHERE user_feed.owner = $1
+        ORDER BY feed.title, feed.id
+        "#,
+        user.id
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(feeds))
 }
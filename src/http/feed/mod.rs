@@ -6,11 +6,17 @@ use super::common::ApiContext;
 pub mod create;
 pub mod discover;
 mod get;
+mod icon;
 mod list;
+mod preview;
+mod search;
 
 pub fn router() -> OpenApiRouter<ApiContext> {
     OpenApiRouter::new()
         .routes(routes!(create::create_feed, discover::discover_feeds))
+        .routes(routes!(preview::preview_feed))
+        .routes(routes!(search::search_feeds))
         .routes(routes!(list::list_feeds))
         .routes(routes!(get::get_feed))
+        .routes(routes!(icon::get_feed_icon))
 }
@@ -0,0 +1,185 @@
+use bind_macros::IntoRequest;
+use utoipa::ToSchema;
+
+use crate::feed::daemon::Daemon;
+use crate::feed::parser::{feed::ParsedFeed, feed_item::ParsedFeedItem};
+use crate::http::common::*;
+use crate::sql::{FeedFormat, FeedItemEnclosure};
+
+/// Request to preview a feed without subscribing to it
+#[derive(Deserialize, Serialize, Validate, IntoRequest, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewFeedRequest {
+    /// URL of the feed to preview
+    #[validate(url)]
+    pub link: String,
+    /// Maximum number of items to include in the preview
+    #[serde(default = "default_item_limit")]
+    #[validate(range(min = 1, max = 100))]
+    pub limit: usize,
+}
+
+fn default_item_limit() -> usize {
+    10
+}
+
+/// Metadata and the first items of a feed which hasn't been stored
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct FeedPreview {
+    /// Format of the feed (RSS, Atom, or JSON)
+    pub format: FeedFormat,
+    /// Link to the website of the feed
+    pub link: String,
+    /// Domain of the website
+    pub domain: Option<String>,
+    /// Title of the feed
+    pub title: String,
+    /// Description of the feed
+    pub description: String,
+    /// URL of the feed's icon
+    pub icon: Option<String>,
+    /// When the feed was last updated according to the feed itself
+    #[schema(format = "date-time")]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Total number of items in the feed
+    pub item_count: usize,
+    /// First items of the feed, in the order the feed lists them
+    pub items: Vec<FeedPreviewItem>,
+}
+
+/// Single item of a previewed feed
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct FeedPreviewItem {
+    /// Unique identifier of the item within the feed
+    pub guid: String,
+    /// Link to the item
+    pub link: Option<String>,
+    /// Title of the item
+    pub title: String,
+    /// Description or summary of the item
+    pub description: Option<String>,
+    /// Name of the item's author
+    pub author: Option<String>,
+    /// Attached media file
+    pub enclosure: Option<FeedItemEnclosure>,
+    /// Full content of the item
+    pub content: Option<String>,
+    /// Categories or tags for the item
+    pub categories: Vec<String>,
+    /// Link to the comments section
+    pub comments_link: Option<String>,
+    /// When the item was published
+    #[schema(format = "date-time")]
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<ParsedFeedItem> for FeedPreviewItem {
+    fn from(item: ParsedFeedItem) -> Self {
+        Self {
+            guid: item.guid,
+            link: item.link,
+            title: item.title,
+            description: item.description,
+            author: item.author,
+            enclosure: item.enclosure,
+            content: item.content,
+            categories: item.categories,
+            comments_link: item.comments_link,
+            published_at: item.published_at,
+        }
+    }
+}
+
+impl FeedPreview {
+    fn from_parsed(feed: ParsedFeed, limit: usize) -> Self {
+        Self {
+            format: feed.format,
+            link: feed.link,
+            domain: feed.domain,
+            title: feed.title,
+            description: feed.description,
+            icon: feed.icon,
+            updated_at: feed.updated_at,
+            item_count: feed.items.len(),
+            items: feed
+                .items
+                .into_iter()
+                .take(limit)
+                .map(FeedPreviewItem::from)
+                .collect(),
+        }
+    }
+}
+
+/// Preview a feed
+///
+/// Fetches and parses the feed, returning its metadata and the first `limit` items.
+/// Unlike creating a feed, nothing is stored in the database, so this can be used to
+/// browse the feeds returned by discovery before subscribing to one of them.
+#[utoipa::path(
+    post,
+    path = "/preview",
+    tag = "feed",
+    request_body = PreviewFeedRequest,
+    responses(
+        (status = 200, description = "Feed fetched and parsed successfully", body = FeedPreview),
+        (status = 400, description = "Invalid request - URL format is invalid or feed could not be fetched"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Feed does not exist"),
+        (status = 500, description = "Internal server error - Feed parsing failed")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn preview_feed(
+    _: AuthUser,
+    Json(body): Json<PreviewFeedRequest>,
+) -> Result<Json<FeedPreview>> {
+    body.validate()?;
+
+    let feed = Daemon::preview_feed(&body.link).await?;
+
+    Ok(Json(FeedPreview::from_parsed(feed, body.limit)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tests::TestContext;
+
+    use super::*;
+    use axum::http::method::Method;
+
+    #[tokio::test]
+    async fn previews_without_storing() {
+        let mut server = mockito::Server::new_async().await;
+        let _feed = server
+            .mock("GET", "/rss")
+            .with_header("Content-Type", "application/rss+xml")
+            .with_body_from_file("tests/feeds/hacker-news-rss.xml")
+            .create_async()
+            .await;
+
+        let ctx = TestContext::new().await;
+        let request = PreviewFeedRequest {
+            link: format!("{}/rss", server.url()),
+            limit: 10,
+        };
+        let response = ctx
+            .req(request.into_request(Method::POST, "/feed/preview"))
+            .await;
+        assert_eq!(response.status(), 200);
+
+        let preview: FeedPreview = ctx.decode(response).await;
+        assert_eq!(preview.format, FeedFormat::Rss);
+        assert_eq!(preview.title, "Hacker News");
+        assert_eq!(preview.item_count, 1);
+        assert_eq!(preview.items.len(), 1);
+
+        let feeds = sqlx::query_scalar!("SELECT COUNT(*) FROM feed")
+            .fetch_one(&ctx.pool)
+            .await
+            .unwrap();
+        assert_eq!(feeds, Some(0));
+    }
+}
@@ -0,0 +1,210 @@
+use utoipa::{IntoParams, ToSchema};
+
+use crate::http::common::*;
+use crate::sql::Feed;
+
+/// Query parameters for searching feeds
+#[derive(Deserialize, Validate, IntoParams)]
+pub struct SearchFeedsQuery {
+    /// Text to search for in the title, description and domain of feeds
+    #[validate(length(min = 1, max = 256))]
+    pub q: String,
+}
+
+/// Feed matching a search, along with its number of subscribers
+#[derive(Serialize, Deserialize, Debug, sqlx::FromRow, ToSchema)]
+pub struct FeedSearchResult {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub feed: Feed,
+    /// Number of users subscribed to the feed
+    pub subscribers: i64,
+}
+
+/// Escapes the wildcards of a `LIKE` pattern so that the text is matched literally
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Search feeds
+///
+/// Searches the feeds already known to the system by their title, description and domain.
+/// Feeds match when any of these contain the text or when the title is similar to it
+/// (trigram similarity), so small typos are tolerated. Results are ranked by the number of
+/// users subscribed to the feed, followed by how similar the title is to the text.
+#[utoipa::path(
+    get,
+    path = "/search",
+    tag = "feed",
+    params(
+        SearchFeedsQuery,
+        Pagination
+    ),
+    responses(
+        (status = 200, description = "Feeds matching the search", body = Vec<FeedSearchResult>),
+        (status = 400, description = "Invalid search or pagination"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn search_feeds(
+    _: AuthUser,
+    State(state): State<ApiContext>,
+    Query(search): Query<SearchFeedsQuery>,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<Vec<FeedSearchResult>>> {
+    search.validate()?;
+    pagination.validate()?;
+
+    let feeds = sqlx::query_as::<_, FeedSearchResult>(
+        r#"
+        SELECT feed.*, COALESCE(subscriptions.count, 0) AS subscribers
+        FROM feed
+        LEFT JOIN (
+            SELECT feed, COUNT(*) AS count FROM user_feed GROUP BY feed
+        ) subscriptions ON subscriptions.feed = feed.id
+        WHERE feed.title ILIKE '%' || $2 || '%'
+            OR feed.description ILIKE '%' || $2 || '%'
+            OR feed.domain ILIKE '%' || $2 || '%'
+            OR feed.title % $1
+        ORDER BY subscribers DESC, similarity(feed.title, $1) DESC, feed.id
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(&search.q)
+    .bind(escape_like(&search.q))
+    .bind(pagination.limit)
+    .bind((pagination.page - 1) * pagination.limit)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(feeds))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sql::InsertUser;
+    use crate::tests::{fixtures::insert_user, TestContext};
+
+    use super::*;
+    use axum::http::method::Method;
+
+    async fn insert_feed(pool: &sqlx::PgPool, link: &str, title: &str, domain: &str) -> i32 {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO feed (format, link, domain, title, description)
+            VALUES ('rss', $1, $2, $3, '')
+            RETURNING id
+            "#,
+            link,
+            domain,
+            title
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    fn search_request(q: &str) -> http::Request<String> {
+        http::Request::builder()
+            .method(Method::GET)
+            .uri(format!("/feed/search?q={}&page=1", q))
+            .body(String::new())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn ranks_by_subscribers() {
+        let ctx = TestContext::new().await;
+
+        let quiet = insert_feed(
+            &ctx.pool,
+            "https://quiet.dev/feed",
+            "Quiet Rust Blog",
+            "quiet.dev",
+        )
+        .await;
+        let popular = insert_feed(
+            &ctx.pool,
+            "https://popular.dev/feed",
+            "Popular Rust Blog",
+            "popular.dev",
+        )
+        .await;
+        insert_feed(
+            &ctx.pool,
+            "https://cooking.com/feed",
+            "Cooking",
+            "cooking.com",
+        )
+        .await;
+
+        let user = InsertUser {
+            email: "test@example.com".to_string(),
+            username: "test".to_string(),
+            providers: vec![],
+            password_hash: None,
+        }
+        .insert(&ctx.pool)
+        .await
+        .unwrap();
+        sqlx::query!(
+            "INSERT INTO user_feed (owner, feed, title) VALUES ($1, $2, 'Popular')",
+            user.id,
+            popular
+        )
+        .execute(&ctx.pool)
+        .await
+        .unwrap();
+
+        let response = ctx.req(search_request("rust")).await;
+        assert_eq!(response.status(), 200);
+
+        let feeds: Vec<FeedSearchResult> = ctx.decode(response).await;
+        let ids = feeds
+            .iter()
+            .map(|result| result.feed.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![popular, quiet]);
+        assert_eq!(feeds[0].subscribers, 1);
+    }
+
+    #[tokio::test]
+    async fn matches_domain() {
+        let ctx = TestContext::new().await;
+
+        let feed = insert_feed(
+            &ctx.pool,
+            "https://cooking.com/feed",
+            "Recipes",
+            "cooking.com",
+        )
+        .await;
+
+        let response = ctx.req(search_request("cooking.com")).await;
+        assert_eq!(response.status(), 200);
+
+        let feeds: Vec<FeedSearchResult> = ctx.decode(response).await;
+        assert_eq!(feeds.len(), 1);
+        assert_eq!(feeds[0].feed.id, feed);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_pages() {
+        let ctx = TestContext::new().await;
+        insert_user(&ctx.pool).await;
+
+        for query in ["q=rust&page=0", "q=rust&page=1&limit=1000"] {
+            let request = http::Request::builder()
+                .uri(format!("/feed/search?{}", query))
+                .body(String::new())
+                .unwrap();
+            assert_eq!(ctx.req(request).await.status(), 400);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use axum::Form;
+
+use super::feeds::{favicons, feeds, groups, Favicon, FeedsGroup, FeverFeed, Group};
+use super::items::{items, saved_item_ids, unread_item_ids, FeverItem, ItemsQuery};
+use super::mark::mark;
+use crate::http::common::*;
+use crate::sql::{subscribed_feed_ids_sql, UserFeverKey};
+
+/// Version of the Fever API implemented
+const API_VERSION: i32 = 3;
+
+#[derive(Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct FeverResponse {
+    pub api_version: i32,
+    /// 1 when the API key is valid, 0 otherwise
+    pub auth: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_refreshed_on_time: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<Group>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feeds: Option<Vec<FeverFeed>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feeds_groups: Option<Vec<FeedsGroup>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub favicons: Option<Vec<Favicon>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<FeverItem>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_items: Option<i64>,
+    /// Comma separated ids
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unread_item_ids: Option<String>,
+    /// Comma separated ids
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saved_item_ids: Option<String>,
+}
+
+/// Parses a number parameter, if present
+pub fn parse_param<T: std::str::FromStr>(
+    params: &HashMap<String, String>,
+    key: &str,
+) -> Result<Option<T>> {
+    params
+        .get(key)
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| Error::BadRequest(format!("Invalid {}", key)))
+        })
+        .transpose()
+}
+
+/// Returns the ids of the feeds the user is subscribed to, directly or through their tags
+async fn subscribed_feeds(pool: &sqlx::PgPool, owner: i32) -> sqlx::Result<Vec<i32>> {
+    sqlx::query_scalar(&subscribed_feed_ids_sql("$1::integer"))
+        .bind(owner)
+        .fetch_all(pool)
+        .await
+}
+
+/// Fever API
+///
+/// Authenticates with the `api_key` form parameter, md5("<email>:<password>") for the password
+/// set with `PUT /user/fever-key`, answering with `auth: 0` when it is invalid. The `mark`
+/// action is applied first, and then the response includes each of the `groups`, `feeds`,
+/// `favicons`, `items`, `unread_item_ids` and `saved_item_ids` parameters present in the
+/// query string.
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "fever",
+    params(
+        ("groups" = Option<String>, Query, description = "Include the user's tags as groups"),
+        ("feeds" = Option<String>, Query, description = "Include the subscribed feeds"),
+        ("favicons" = Option<String>, Query, description = "Include the icons of the feeds"),
+        ("items" = Option<String>, Query, description = "Include up to 50 items"),
+        ("since_id" = Option<i64>, Query, description = "Items after the id, oldest first"),
+        ("max_id" = Option<i64>, Query, description = "Items before the id, newest first"),
+        ("with_ids" = Option<String>, Query, description = "Comma separated ids of the items"),
+        ("unread_item_ids" = Option<String>, Query, description = "Include the ids of the unread items"),
+        ("saved_item_ids" = Option<String>, Query, description = "Include the ids of the saved items")
+    ),
+    request_body(content = String, content_type = "application/x-www-form-urlencoded",
+        description = "`api_key`, and `mark`, `as`, `id` and `before` to change items"),
+    responses(
+        (status = 200, description = "Requested data", body = FeverResponse),
+        (status = 400, description = "Invalid parameters"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn fever(
+    State(state): State<ApiContext>,
+    Query(query): Query<HashMap<String, String>>,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<Json<FeverResponse>> {
+    let params: HashMap<String, String> = query.into_iter().chain(form).collect();
+    let mut response = FeverResponse {
+        api_version: API_VERSION,
+        ..Default::default()
+    };
+
+    let owner = match params.get("api_key") {
+        Some(api_key) => UserFeverKey::get_owner(&state.pool, api_key).await?,
+        None => None,
+    };
+    let Some(owner) = owner else {
+        return Ok(Json(response));
+    };
+    response.auth = 1;
+    response.last_refreshed_on_time = Some(chrono::Utc::now().timestamp());
+
+    let pool = &state.pool;
+    let feed_ids = subscribed_feeds(pool, owner).await?;
+
+    if params.contains_key("mark") {
+        mark(pool, owner, &feed_ids, &params, &mut response).await?;
+    }
+    if params.contains_key("groups") || params.contains_key("feeds") {
+        let (tag_groups, feeds_groups) = groups(pool, owner).await?;
+        if params.contains_key("groups") {
+            response.groups = Some(tag_groups);
+        }
+        response.feeds_groups = Some(feeds_groups);
+    }
+    if params.contains_key("feeds") {
+        response.feeds = Some(feeds(pool, owner, &feed_ids).await?);
+    }
+    if params.contains_key("favicons") {
+        response.favicons = Some(favicons(pool, &feed_ids).await?);
+    }
+    if params.contains_key("items") {
+        let with_ids = params
+            .get("with_ids")
+            .map(|ids| {
+                ids.split(',')
+                    .map(|id| id.trim().parse())
+                    .collect::<Result<Vec<i64>, _>>()
+                    .map_err(|_| Error::BadRequest("Invalid with_ids".to_string()))
+            })
+            .transpose()?;
+        let query = ItemsQuery {
+            with_ids,
+            since_id: parse_param(&params, "since_id")?,
+            max_id: parse_param(&params, "max_id")?,
+        };
+        let (items, total) = items(pool, owner, &feed_ids, &query).await?;
+        response.items = Some(items);
+        response.total_items = Some(total);
+    }
+    if params.contains_key("unread_item_ids") {
+        response.unread_item_ids = Some(unread_item_ids(pool, owner, &feed_ids).await?);
+    }
+    if params.contains_key("saved_item_ids") {
+        response.saved_item_ids = Some(saved_item_ids(pool, owner).await?);
+    }
+
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    async fn fever_request(ctx: &TestContext, query: &str, body: &str) -> FeverResponse {
+        let request = http::Request::builder()
+            .method(http::Method::POST)
+            .uri(format!("/fever?api&{}", query))
+            .header(
+                http::header::CONTENT_TYPE,
+                "application/x-www-form-urlencoded",
+            )
+            .body(body.to_string())
+            .unwrap();
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 200);
+        ctx.decode(response).await
+    }
+
+    #[tokio::test]
+    async fn serves_fever_clients() {
+        let ctx = TestContext::new().await;
+        insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        let other = insert_feed(&ctx.pool, "https://other.com/feed", "Other").await;
+        let unsubscribed = insert_feed(&ctx.pool, "https://unsubscribed.com/feed", "No").await;
+        insert_subscription(&ctx.pool, 1, feed).await;
+        let tag = insert_tag(&ctx.pool, 1, "News", &[other]).await;
+        let first = insert_item(&ctx.pool, feed, "First", "").await;
+        let second = insert_item(&ctx.pool, other, "Second", "").await;
+        let third = insert_item(&ctx.pool, feed, "Third", "").await;
+        insert_item(&ctx.pool, unsubscribed, "Hidden", "").await;
+
+        let request = http::Request::builder()
+            .method(http::Method::PUT)
+            .uri("/user/fever-key")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::json!({ "password": "fever-password" }).to_string())
+            .unwrap();
+        assert_eq!(ctx.req(request).await.status(), 204);
+        let api_key =
+            sqlx::query_scalar!(r#"SELECT md5('test@example.com:fever-password') AS "key!""#)
+                .fetch_one(&ctx.pool)
+                .await
+                .unwrap();
+        let auth = format!("api_key={}", api_key);
+
+        let response = fever_request(&ctx, "groups", "api_key=wrong").await;
+        assert_eq!(response.auth, 0);
+        assert!(response.groups.is_none());
+
+        let response = fever_request(&ctx, "groups&feeds", &auth).await;
+        assert_eq!(response.auth, 1);
+        assert_eq!(response.groups.unwrap()[0].title, "News");
+        let feeds: Vec<i32> = response.feeds.unwrap().iter().map(|f| f.id).collect();
+        assert_eq!(feeds, vec![feed, other]);
+        let feeds_groups = response.feeds_groups.unwrap();
+        assert_eq!(feeds_groups[0].group_id, tag);
+        assert_eq!(feeds_groups[0].feed_ids, other.to_string());
+
+        let response = fever_request(&ctx, &format!("items&since_id={}", first), &auth).await;
+        let items: Vec<i64> = response.items.unwrap().iter().map(|i| i.id).collect();
+        assert_eq!(items, vec![second, third]);
+        assert_eq!(response.total_items, Some(3));
+        let response = fever_request(&ctx, &format!("items&max_id={}", third), &auth).await;
+        let items: Vec<i64> = response.items.unwrap().iter().map(|i| i.id).collect();
+        assert_eq!(items, vec![second, first]);
+
+        let body = format!("{}&mark=item&as=saved&id={}", auth, second);
+        let response = fever_request(&ctx, "", &body).await;
+        assert_eq!(response.saved_item_ids, Some(second.to_string()));
+
+        let body = format!("{}&mark=feed&as=read&id={}", auth, feed);
+        let response = fever_request(&ctx, "", &body).await;
+        assert_eq!(response.unread_item_ids, Some(second.to_string()));
+
+        let body = format!("{}&mark=item&as=unread&id={}", auth, first);
+        fever_request(&ctx, "", &body).await;
+        let response = fever_request(&ctx, "unread_item_ids&saved_item_ids", &auth).await;
+        assert_eq!(
+            response.unread_item_ids,
+            Some(format!("{},{}", first, second))
+        );
+        assert_eq!(response.saved_item_ids, Some(second.to_string()));
+
+        let body = format!("{}&mark=group&as=read&id=0", auth);
+        let response = fever_request(&ctx, "", &body).await;
+        assert_eq!(response.unread_item_ids, Some(String::new()));
+    }
+}
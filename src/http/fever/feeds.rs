@@ -0,0 +1,151 @@
+use crate::http::common::*;
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Group {
+    pub id: i32,
+    pub title: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct FeedsGroup {
+    pub group_id: i32,
+    /// Comma separated ids of the feeds in the group
+    pub feed_ids: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct FeverFeed {
+    pub id: i32,
+    /// Id of the feed's favicon, 0 when it has none
+    pub favicon_id: i32,
+    pub title: String,
+    pub url: String,
+    pub site_url: String,
+    pub is_spark: i32,
+    pub last_updated_on_time: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Favicon {
+    pub id: i32,
+    /// Image as `<mime type>;base64,<data>`
+    pub data: String,
+}
+
+/// Returns the user's tags as groups, along with the feeds in each of them
+pub async fn groups(
+    pool: &sqlx::PgPool,
+    owner: i32,
+) -> sqlx::Result<(Vec<Group>, Vec<FeedsGroup>)> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, title, ARRAY(
+            SELECT (child).id FROM unnest(children) AS child WHERE (child).type = 'feed' ORDER BY 1
+        ) AS "feeds!: Vec<i32>"
+        FROM user_tag WHERE owner = $1 ORDER BY id
+        "#,
+        owner
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let feeds_groups = rows
+        .iter()
+        .map(|row| FeedsGroup {
+            group_id: row.id,
+            feed_ids: join_ids(&row.feeds),
+        })
+        .collect();
+    let groups = rows
+        .into_iter()
+        .map(|row| Group {
+            id: row.id,
+            title: row.title,
+        })
+        .collect();
+    Ok((groups, feeds_groups))
+}
+
+/// Returns the feeds of the user's tag, if they own it
+pub async fn group_feeds(pool: &sqlx::PgPool, owner: i32, group: i32) -> sqlx::Result<Vec<i32>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT (child).id AS "id!" FROM user_tag, unnest(user_tag.children) AS child
+        WHERE user_tag.id = $1 AND user_tag.owner = $2 AND (child).type = 'feed'
+        "#,
+        group,
+        owner
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Returns the feeds, using the titles the user gave them
+pub async fn feeds(pool: &sqlx::PgPool, owner: i32, ids: &[i32]) -> sqlx::Result<Vec<FeverFeed>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT feed.id, COALESCE(user_feed.title, feed.title) AS "title!", feed.link, feed.domain,
+            feed.updated_at, feed_icon.id AS "icon?"
+        FROM feed
+        LEFT JOIN user_feed ON user_feed.feed = feed.id AND user_feed.owner = $1
+        LEFT JOIN feed_icon ON feed_icon.feed = feed.id AND feed_icon.data IS NOT NULL
+        WHERE feed.id = ANY($2)
+        ORDER BY feed.id
+        "#,
+        owner,
+        ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| FeverFeed {
+            id: row.id,
+            favicon_id: row.icon.unwrap_or(0),
+            title: row.title,
+            site_url: row
+                .domain
+                .map(|domain| format!("https://{}", domain))
+                .unwrap_or_else(|| row.link.clone()),
+            url: row.link,
+            is_spark: 0,
+            last_updated_on_time: row.updated_at.timestamp(),
+        })
+        .collect())
+}
+
+/// Returns the icons of the feeds which have one
+pub async fn favicons(pool: &sqlx::PgPool, feeds: &[i32]) -> sqlx::Result<Vec<Favicon>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, content_type, translate(encode(data, 'base64'), E'\n', '') AS "data!"
+        FROM feed_icon
+        WHERE feed = ANY($1) AND data IS NOT NULL
+        ORDER BY id
+        "#,
+        feeds
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Favicon {
+            id: row.id,
+            data: format!(
+                "{};base64,{}",
+                row.content_type.as_deref().unwrap_or("image/x-icon"),
+                row.data
+            ),
+        })
+        .collect())
+}
+
+/// Joins ids with commas, as Fever lists them
+pub fn join_ids<T: ToString>(ids: &[T]) -> String {
+    ids.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
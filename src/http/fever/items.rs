@@ -0,0 +1,124 @@
+use super::feeds::join_ids;
+use crate::http::common::*;
+
+/// Most items returned at once
+const MAX_ITEMS: i64 = 50;
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct FeverItem {
+    pub id: i64,
+    pub feed_id: i32,
+    pub title: String,
+    pub author: String,
+    pub html: String,
+    pub url: String,
+    pub is_saved: i32,
+    pub is_read: i32,
+    /// UNIX timestamp of when the item was published
+    pub created_on_time: i64,
+}
+
+/// Which items to return, from the `with_ids`, `since_id` and `max_id` parameters
+#[derive(Debug, Default)]
+pub struct ItemsQuery {
+    /// Only the items with the ids
+    pub with_ids: Option<Vec<i64>>,
+    /// Items after the id, oldest first
+    pub since_id: Option<i64>,
+    /// Items before the id, newest first
+    pub max_id: Option<i64>,
+}
+
+/// Returns up to 50 items of the feeds, along with the total number of items in the feeds
+pub async fn items(
+    pool: &sqlx::PgPool,
+    owner: i32,
+    feeds: &[i32],
+    query: &ItemsQuery,
+) -> sqlx::Result<(Vec<FeverItem>, i64)> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT feed_item.id, feed_item.feed_id, feed_item.title, feed_item.author,
+            COALESCE(feed_item.content, feed_item.description, '') AS "html!",
+            feed_item.link,
+            COALESCE(feed_item.published_at, feed_item.created_at) AS "created!",
+            COALESCE(user_item_state.read, false) AS "read!",
+            COALESCE(user_item_state.starred, false) AS "starred!"
+        FROM feed_item
+        LEFT JOIN user_item_state ON user_item_state.item = feed_item.id
+            AND user_item_state.owner = $1
+        WHERE feed_item.feed_id = ANY($2)
+            AND ($3::bigint[] IS NULL OR feed_item.id = ANY($3))
+            AND ($4::bigint IS NULL OR feed_item.id > $4)
+            AND ($5::bigint IS NULL OR feed_item.id < $5)
+        ORDER BY CASE WHEN $5::bigint IS NULL THEN feed_item.id ELSE -feed_item.id END
+        LIMIT $6
+        "#,
+        owner,
+        feeds,
+        query.with_ids.as_deref(),
+        query.since_id,
+        query.max_id,
+        MAX_ITEMS
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM feed_item WHERE feed_id = ANY($1)"#,
+        feeds
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let items = rows
+        .into_iter()
+        .map(|row| FeverItem {
+            id: row.id,
+            feed_id: row.feed_id,
+            title: row.title,
+            author: row.author.unwrap_or_default(),
+            html: row.html,
+            url: row.link.unwrap_or_default(),
+            is_saved: row.starred as i32,
+            is_read: row.read as i32,
+            created_on_time: row.created.timestamp(),
+        })
+        .collect();
+    Ok((items, total))
+}
+
+/// Returns the comma separated ids of the unread items of the feeds
+pub async fn unread_item_ids(
+    pool: &sqlx::PgPool,
+    owner: i32,
+    feeds: &[i32],
+) -> sqlx::Result<String> {
+    let ids = sqlx::query_scalar!(
+        r#"
+        SELECT feed_item.id FROM feed_item
+        WHERE feed_item.feed_id = ANY($2) AND NOT EXISTS (
+            SELECT 1 FROM user_item_state
+            WHERE user_item_state.item = feed_item.id AND user_item_state.owner = $1
+                AND user_item_state.read
+        )
+        ORDER BY feed_item.id
+        "#,
+        owner,
+        feeds
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(join_ids(&ids))
+}
+
+/// Returns the comma separated ids of the user's starred items
+pub async fn saved_item_ids(pool: &sqlx::PgPool, owner: i32) -> sqlx::Result<String> {
+    let ids = sqlx::query_scalar!(
+        "SELECT item FROM user_item_state WHERE owner = $1 AND starred ORDER BY item",
+        owner
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(join_ids(&ids))
+}
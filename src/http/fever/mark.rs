@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use super::api::{parse_param, FeverResponse};
+use super::feeds::group_feeds;
+use super::items::{saved_item_ids, unread_item_ids};
+use crate::http::common::*;
+use crate::sql::UserItemState;
+
+/// Group which clients use for all the feeds
+const ALL_FEEDS_GROUP: i32 = 0;
+
+/// Applies the `mark` action: marking an item read, unread, saved or unsaved, or marking the
+/// items of a feed or group published before the `before` timestamp read. The ids of the
+/// unread or saved items are added to the response, as they changed
+pub async fn mark(
+    pool: &sqlx::PgPool,
+    owner: i32,
+    feeds: &[i32],
+    params: &HashMap<String, String>,
+    response: &mut FeverResponse,
+) -> Result<()> {
+    let mark = params.get("mark").map(String::as_str);
+    let action = params.get("as").map(String::as_str);
+    let id: i64 =
+        parse_param(params, "id")?.ok_or_else(|| Error::BadRequest("Missing id".to_string()))?;
+    let before = parse_param(params, "before")?
+        .and_then(|before| chrono::DateTime::from_timestamp(before, 0))
+        .unwrap_or_else(chrono::Utc::now);
+
+    match (mark, action) {
+        (Some("item"), Some(action @ ("read" | "unread"))) => {
+            UserItemState::set_read(pool, owner, &[id], action == "read").await?;
+            response.unread_item_ids = Some(unread_item_ids(pool, owner, feeds).await?);
+        }
+        (Some("item"), Some(action @ ("saved" | "unsaved"))) => {
+            UserItemState::set_starred(pool, owner, &[id], action == "saved").await?;
+            response.saved_item_ids = Some(saved_item_ids(pool, owner).await?);
+        }
+        (Some("feed"), Some("read")) => {
+            let feed =
+                i32::try_from(id).map_err(|_| Error::BadRequest("Invalid id".to_string()))?;
+            if feeds.contains(&feed) {
+                UserItemState::mark_feeds_read(pool, owner, &[feed], before).await?;
+            }
+            response.unread_item_ids = Some(unread_item_ids(pool, owner, feeds).await?);
+        }
+        (Some("group"), Some("read")) => {
+            let group =
+                i32::try_from(id).map_err(|_| Error::BadRequest("Invalid id".to_string()))?;
+            let group_feeds = match group {
+                ALL_FEEDS_GROUP => feeds.to_vec(),
+                _ => group_feeds(pool, owner, group).await?,
+            };
+            UserItemState::mark_feeds_read(pool, owner, &group_feeds, before).await?;
+            response.unread_item_ids = Some(unread_item_ids(pool, owner, feeds).await?);
+        }
+        _ => return Err(Error::BadRequest("Invalid mark action".to_string())),
+    }
+    Ok(())
+}
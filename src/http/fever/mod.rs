@@ -0,0 +1,20 @@
+//! Fever compatible API, for third-party clients which only speak it. Every request is a
+//! `POST` to the same endpoint with the user's `api_key`, and the query string selects what
+//! is returned, such as `?api&items&since_id=42`.
+//!
+//! Groups are the user's tags, feeds are the feeds they are subscribed to and saved items are
+//! their starred items.
+
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use super::common::ApiContext;
+
+mod api;
+mod feeds;
+mod items;
+mod mark;
+
+pub fn router() -> OpenApiRouter<ApiContext> {
+    OpenApiRouter::new().routes(routes!(api::fever))
+}
@@ -0,0 +1,211 @@
+use axum::{extract::FromRequestParts, http::request::Parts, Form};
+
+use crate::auth::password::verify_password;
+use crate::auth::user::AuthUserClaims;
+use crate::http::common::*;
+use crate::sql::User;
+
+/// User authenticated with the `Authorization: GoogleLogin auth=<token>` header of Google
+/// Reader clients, where the token is the one returned by ClientLogin
+pub struct GReaderUser(pub AuthUser);
+
+impl FromRequestParts<ApiContext> for GReaderUser {
+    type Rejection = http::StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &ApiContext,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(http::header::AUTHORIZATION)
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| header.strip_prefix("GoogleLogin auth="))
+            .map(|token| token.trim().to_string());
+
+        // In tests, mock a valid token when the Authorization header is missing
+        #[cfg(test)]
+        let token = Some(token.unwrap_or_else(|| {
+            AuthUserClaims::new(1, "test@example.com".to_string(), "test".to_string())
+                .to_jwt(&state.jwt_secret)
+                .unwrap()
+        }));
+
+        let token = token.ok_or(http::StatusCode::UNAUTHORIZED)?;
+        let claims = AuthUserClaims::from_jwt(&token, &state.jwt_secret).map_err(|e| {
+            tracing::debug!("failed to parse GoogleLogin token: {}", e);
+            http::StatusCode::UNAUTHORIZED
+        })?;
+
+        Ok(Self(claims.into()))
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ClientLoginRequest {
+    /// Email or username of the user
+    #[serde(rename = "Email")]
+    email: String,
+    #[serde(rename = "Passwd")]
+    passwd: String,
+}
+
+/// Login
+///
+/// Authenticates with an email or username and password, returning the token to pass as
+/// `Authorization: GoogleLogin auth=<token>` in the `Auth` line of a plain text response.
+#[utoipa::path(
+    post,
+    path = "/accounts/ClientLogin",
+    tag = "greader",
+    request_body(content = ClientLoginRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Login successful", body = String, content_type = "text/plain"),
+        (status = 401, description = "Login failed", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn client_login(
+    State(state): State<ApiContext>,
+    Form(body): Form<ClientLoginRequest>,
+) -> Result<impl IntoResponse> {
+    let user = match User::get_by_email(&state.pool, &body.email).await {
+        Ok(user) => Some(user),
+        Err(_) => User::get_by_username(&state.pool, &body.email).await.ok(),
+    };
+
+    let verified = match user.as_ref().and_then(|user| user.password_hash.as_ref()) {
+        Some(hash) => verify_password(&body.passwd, hash).map_err(|err| anyhow::anyhow!(err))?,
+        None => false,
+    };
+    let Some(user) = user.filter(|_| verified) else {
+        return Ok((
+            http::StatusCode::UNAUTHORIZED,
+            "Error=BadAuthentication\n".to_string(),
+        ));
+    };
+
+    let claims: AuthUserClaims = user.into();
+    let token = claims
+        .to_jwt(&state.jwt_secret)
+        .map_err(|err| anyhow::anyhow!(err))?;
+    Ok((
+        http::StatusCode::OK,
+        format!("SID={0}\nLSID={0}\nAuth={0}\n", token),
+    ))
+}
+
+/// Get an edit token
+///
+/// Clients send this token with edits. Requests are already authenticated by their
+/// `Authorization` header, so it isn't checked.
+#[utoipa::path(
+    get,
+    path = "/reader/api/0/token",
+    tag = "greader",
+    responses(
+        (status = 200, description = "Edit token", body = String, content_type = "text/plain"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("GoogleLogin" = [])
+    )
+)]
+pub async fn token(GReaderUser(user): GReaderUser) -> String {
+    format!("{:0>57}", user.id)
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserInfo {
+    user_id: String,
+    user_name: String,
+    user_profile_id: String,
+    user_email: String,
+}
+
+/// Get the user
+#[utoipa::path(
+    get,
+    path = "/reader/api/0/user-info",
+    tag = "greader",
+    responses(
+        (status = 200, description = "The authenticated user", body = UserInfo),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("GoogleLogin" = [])
+    )
+)]
+pub async fn user_info(GReaderUser(user): GReaderUser) -> Json<UserInfo> {
+    Json(UserInfo {
+        user_id: user.id.to_string(),
+        user_name: user.username,
+        user_profile_id: user.id.to_string(),
+        user_email: user.email,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use http_body_util::BodyExt;
+
+    use crate::auth::password::hash_password;
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn logs_in() {
+        let ctx = TestContext::new().await;
+        insert_user(&ctx.pool).await;
+        sqlx::query!(
+            "UPDATE \"user\" SET password_hash = $1",
+            hash_password("hunter2").unwrap()
+        )
+        .execute(&ctx.pool)
+        .await
+        .unwrap();
+
+        let login = |password: &str| {
+            http::Request::builder()
+                .method(http::Method::POST)
+                .uri("/greader/accounts/ClientLogin")
+                .header(
+                    http::header::CONTENT_TYPE,
+                    "application/x-www-form-urlencoded",
+                )
+                .body(format!("Email=test&Passwd={}", password))
+                .unwrap()
+        };
+        let response = ctx.req(login("wrong")).await;
+        assert_eq!(response.status(), 401);
+
+        let response = ctx.req(login("hunter2")).await;
+        assert_eq!(response.status(), 200);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let token = std::str::from_utf8(&body)
+            .unwrap()
+            .lines()
+            .find_map(|line| line.strip_prefix("Auth="))
+            .unwrap();
+
+        let request = http::Request::builder()
+            .uri("/greader/reader/api/0/user-info")
+            .header(
+                http::header::AUTHORIZATION,
+                format!("GoogleLogin auth={}", token),
+            )
+            .body(String::new())
+            .unwrap();
+        let info: UserInfo = ctx.decode(ctx.req(request).await).await;
+        assert_eq!(info.user_name, "test");
+
+        let request = http::Request::builder()
+            .uri("/greader/reader/api/0/user-info")
+            .header(http::header::AUTHORIZATION, "GoogleLogin auth=invalid")
+            .body(String::new())
+            .unwrap();
+        assert_eq!(ctx.req(request).await.status(), 401);
+    }
+}
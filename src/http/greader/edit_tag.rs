@@ -0,0 +1,170 @@
+use axum::Form;
+
+use super::auth::GReaderUser;
+use super::ids::StreamId;
+use super::stream::parse_item_ids;
+use super::Params;
+use crate::http::common::*;
+use crate::sql::UserItemState;
+
+/// Tag items
+///
+/// Adds the states of the `a` parameters to the items of the `i` parameters and removes the
+/// ones of the `r` parameters, where the states are `user/-/state/com.google/read` and
+/// `user/-/state/com.google/starred`. Other tags are ignored.
+#[utoipa::path(
+    post,
+    path = "/reader/api/0/edit-tag",
+    tag = "greader",
+    request_body(content = String, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Items tagged", body = String, content_type = "text/plain"),
+        (status = 400, description = "Invalid item id"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("GoogleLogin" = [])
+    )
+)]
+pub async fn edit_tag(
+    GReaderUser(user): GReaderUser,
+    State(state): State<ApiContext>,
+    Query(query): Query<Vec<(String, String)>>,
+    Form(form): Form<Vec<(String, String)>>,
+) -> Result<&'static str> {
+    let params = Params::new(query, form);
+    let items = parse_item_ids(&params)?;
+
+    let changes = params
+        .get_all("a")
+        .map(|tag| (tag, true))
+        .chain(params.get_all("r").map(|tag| (tag, false)));
+    for (tag, added) in changes {
+        match StreamId::parse(tag) {
+            Some(StreamId::Read) => {
+                UserItemState::set_read(&state.pool, user.id, &items, added).await?;
+            }
+            Some(StreamId::Starred) => {
+                UserItemState::set_starred(&state.pool, user.id, &items, added).await?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok("OK")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::http::greader::ids::long_item_id;
+    use crate::http::greader::stream::{ItemRefs, StreamContents};
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    fn get(uri: &str) -> http::Request<String> {
+        http::Request::builder()
+            .uri(format!("/greader/reader/api/0{}", uri))
+            .body(String::new())
+            .unwrap()
+    }
+
+    fn post(uri: &str, body: String) -> http::Request<String> {
+        http::Request::builder()
+            .method(http::Method::POST)
+            .uri(format!("/greader/reader/api/0{}", uri))
+            .header(
+                http::header::CONTENT_TYPE,
+                "application/x-www-form-urlencoded",
+            )
+            .body(body)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn streams_and_tags_items() {
+        let ctx = TestContext::new().await;
+        insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        let other = insert_feed(&ctx.pool, "https://other.com/feed", "Other").await;
+        insert_subscription(&ctx.pool, 1, feed).await;
+        insert_tag(&ctx.pool, 1, "News", &[other]).await;
+        let first = insert_item(&ctx.pool, feed, "First", "").await;
+        let second = insert_item(&ctx.pool, feed, "Second", "").await;
+        let tagged = insert_item(&ctx.pool, other, "Tagged", "").await;
+
+        let ids: ItemRefs = ctx
+            .decode(
+                ctx.req(get(
+                    "/stream/items/ids?s=user/-/state/com.google/reading-list&n=2",
+                ))
+                .await,
+            )
+            .await;
+        let refs: Vec<String> = ids.item_refs.iter().map(|r| r.id.clone()).collect();
+        assert_eq!(refs, vec![tagged.to_string(), second.to_string()]);
+        let uri = format!(
+            "/stream/items/ids?s=user/-/state/com.google/reading-list&n=2&c={}",
+            ids.continuation.unwrap()
+        );
+        let ids: ItemRefs = ctx.decode(ctx.req(get(&uri)).await).await;
+        assert_eq!(ids.item_refs[0].id, first.to_string());
+        assert!(ids.continuation.is_none());
+
+        let body = format!(
+            "i={}&i={}&a=user%2F-%2Fstate%2Fcom.google%2Fread&a=user%2F-%2Fstate%2Fcom.google%2Fstarred",
+            long_item_id(first),
+            tagged
+        );
+        let response = ctx.req(post("/edit-tag", body)).await;
+        assert_eq!(response.status(), 200);
+
+        let contents: StreamContents = ctx
+            .decode(
+                ctx.req(get(
+                    "/stream/contents/user/-/state/com.google/reading-list?xt=user/-/state/com.google/read",
+                ))
+                .await,
+            )
+            .await;
+        let titles: Vec<&str> = contents.items.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["Second"]);
+
+        let contents: StreamContents = ctx
+            .decode(ctx.req(get("/stream/contents/user/-/label/News")).await)
+            .await;
+        assert_eq!(contents.items.len(), 1);
+        assert_eq!(contents.items[0].id, long_item_id(tagged));
+        assert!(contents.items[0]
+            .categories
+            .contains(&"user/-/state/com.google/starred".to_string()));
+        assert_eq!(
+            contents.items[0].origin.stream_id,
+            format!("feed/{}", other)
+        );
+
+        let body = format!("i={}&r=user%2F-%2Fstate%2Fcom.google%2Fstarred", tagged);
+        ctx.req(post("/edit-tag", body)).await;
+        let contents: StreamContents = ctx
+            .decode(
+                ctx.req(get("/stream/contents?s=user/-/state/com.google/starred"))
+                    .await,
+            )
+            .await;
+        let titles: Vec<&str> = contents.items.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["First"]);
+
+        // Items of feeds the user isn't subscribed to are left out
+        let unsubscribed = insert_feed(&ctx.pool, "https://private.com/feed", "Private").await;
+        let hidden = insert_item(&ctx.pool, unsubscribed, "Hidden", "").await;
+        let body = format!("i={}&i={}&i={}", first, second, hidden);
+        let contents: StreamContents = ctx
+            .decode(ctx.req(post("/stream/items/contents", body)).await)
+            .await;
+        assert_eq!(contents.items.len(), 2);
+
+        let response = ctx.req(get("/stream/contents?s=splice/1")).await;
+        assert_eq!(response.status(), 403);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::query::SqlParams;
+use crate::sql::subscribed_feeds_sql;
+
+/// Prefix of the long form of item ids, which is followed by the id as 16 hex digits
+const LONG_ITEM_ID_PREFIX: &str = "tag:google.com,2005:reader/item/";
+
+pub const READING_LIST: &str = "user/-/state/com.google/reading-list";
+pub const STARRED: &str = "user/-/state/com.google/starred";
+pub const READ: &str = "user/-/state/com.google/read";
+
+/// Returns the long form of an item id, used in stream contents
+pub fn long_item_id(id: i64) -> String {
+    format!("{}{:016x}", LONG_ITEM_ID_PREFIX, id as u64)
+}
+
+/// Parses an item id in either the long form or the short, decimal form
+pub fn parse_item_id(id: &str) -> Option<i64> {
+    match id.strip_prefix(LONG_ITEM_ID_PREFIX) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok().map(|id| id as i64),
+        None => id.parse().ok(),
+    }
+}
+
+/// Returns the id of the label stream of a tag
+pub fn label_id(title: &str) -> String {
+    format!("user/-/label/{}", title)
+}
+
+/// Returns the id of the stream of a feed
+pub fn feed_id(id: i32) -> String {
+    format!("feed/{}", id)
+}
+
+/// A stream of items, named by clients with ids such as `feed/1`, `user/-/label/News` or
+/// `user/-/state/com.google/starred`
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamId {
+    /// Every item of the user's subscriptions
+    ReadingList,
+    Starred,
+    Read,
+    /// Items of the feeds in the user's tag with the title
+    Label(String),
+    Feed(i32),
+}
+
+impl StreamId {
+    pub fn parse(id: &str) -> Option<Self> {
+        if let Some(feed) = id.strip_prefix("feed/") {
+            return feed.parse().ok().map(StreamId::Feed);
+        }
+
+        // Clients may use the user's id in place of `-`
+        let (_, rest) = id.strip_prefix("user/")?.split_once('/')?;
+        match rest {
+            "state/com.google/reading-list" => Some(StreamId::ReadingList),
+            "state/com.google/starred" => Some(StreamId::Starred),
+            "state/com.google/read" => Some(StreamId::Read),
+            _ => rest
+                .strip_prefix("label/")
+                .filter(|title| !title.is_empty())
+                .map(|title| StreamId::Label(title.to_string())),
+        }
+    }
+
+    /// Returns a predicate over `feed_item` matching the items of the stream, where `user` is
+    /// the placeholder of the user's id, cast to an integer
+    pub fn condition_sql(&self, user: &str, params: &mut SqlParams) -> String {
+        match self {
+            StreamId::ReadingList => subscribed_feeds_sql(user),
+            StreamId::Starred | StreamId::Read => format!(
+                "EXISTS (SELECT 1 FROM user_item_state AS state WHERE state.item = feed_item.id \
+                AND state.owner = {} AND state.{})",
+                user,
+                match self {
+                    StreamId::Starred => "starred",
+                    _ => "read",
+                }
+            ),
+            StreamId::Label(title) => format!(
+                "feed_item.feed_id IN (SELECT (child).id FROM user_tag, unnest(user_tag.children) \
+                AS child WHERE user_tag.owner = {} AND lower(user_tag.title) = lower({}) \
+                AND (child).type = 'feed')",
+                user,
+                params.push(title)
+            ),
+            StreamId::Feed(feed) => format!(
+                "feed_item.feed_id = {}::integer AND {}",
+                params.push(feed),
+                subscribed_feeds_sql(user)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_item_ids() {
+        let long = long_item_id(42);
+        assert_eq!(long, "tag:google.com,2005:reader/item/000000000000002a");
+        assert_eq!(parse_item_id(&long), Some(42));
+        assert_eq!(parse_item_id("42"), Some(42));
+        assert_eq!(parse_item_id("tag:google.com,2005:reader/item/zz"), None);
+        assert_eq!(parse_item_id("abc"), None);
+    }
+
+    #[test]
+    fn parses_stream_ids() {
+        assert_eq!(StreamId::parse(READING_LIST), Some(StreamId::ReadingList));
+        assert_eq!(
+            StreamId::parse("user/1/state/com.google/starred"),
+            Some(StreamId::Starred)
+        );
+        assert_eq!(StreamId::parse(READ), Some(StreamId::Read));
+        assert_eq!(
+            StreamId::parse(&label_id("Tech News")),
+            Some(StreamId::Label("Tech News".to_string()))
+        );
+        assert_eq!(StreamId::parse(&feed_id(3)), Some(StreamId::Feed(3)));
+        assert_eq!(StreamId::parse("feed/https://example.com"), None);
+        assert_eq!(StreamId::parse("user/-/label/"), None);
+        assert_eq!(StreamId::parse("splice/1"), None);
+    }
+}
@@ -0,0 +1,60 @@
+//! Google Reader compatible API, for third-party clients which only speak it. Clients log in
+//! with ClientLogin and then send the token as `Authorization: GoogleLogin auth=<token>`.
+//!
+//! Subscriptions are the user's `user_feed` rows, labels are their tags and the read and
+//! starred states are their `user_item_state` rows.
+
+use axum::routing::get;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use super::common::ApiContext;
+
+mod auth;
+mod edit_tag;
+mod ids;
+mod stream;
+mod subscription;
+mod tag;
+
+/// Parameters of a request, from its query string or form body. Lists, such as the ids of
+/// items, are sent by repeating their key, so the pairs are kept as they are
+#[derive(Debug, Default)]
+pub struct Params(Vec<(String, String)>);
+
+impl Params {
+    pub fn new(query: Vec<(String, String)>, form: Vec<(String, String)>) -> Self {
+        Self(query.into_iter().chain(form).collect())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.get_all(key).next()
+    }
+
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> {
+        self.0
+            .iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+pub fn router() -> OpenApiRouter<ApiContext> {
+    OpenApiRouter::new()
+        .routes(routes!(auth::client_login))
+        .routes(routes!(auth::token))
+        .routes(routes!(auth::user_info))
+        .routes(routes!(subscription::list_subscriptions))
+        .routes(routes!(subscription::edit_subscription))
+        .routes(routes!(subscription::quick_add))
+        .routes(routes!(tag::list_tags))
+        .routes(routes!(stream::stream_contents))
+        // Most clients put the stream id in the path rather than the `s` parameter
+        .route(
+            "/reader/api/0/stream/contents/{*stream}",
+            get(stream::stream_contents_by_path),
+        )
+        .routes(routes!(stream::stream_item_ids))
+        .routes(routes!(stream::item_contents))
+        .routes(routes!(edit_tag::edit_tag))
+}
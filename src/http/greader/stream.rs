@@ -0,0 +1,450 @@
+use axum::Form;
+
+use super::auth::GReaderUser;
+use super::ids::{feed_id, long_item_id, parse_item_id, StreamId, READ, READING_LIST, STARRED};
+use super::Params;
+use crate::http::common::*;
+use crate::query::SqlParams;
+use crate::sql::{
+    subscribed_feeds_sql, Cursor, CursorPagination, Page, RowOrder, SortKeyType, TimelineItem,
+    PUBLISHED_AT_SQL,
+};
+
+/// Most items returned at once, clients get the rest with the continuation
+const MAX_ITEMS: i64 = 100;
+
+/// Which items of a stream to return, from the parameters shared by the stream endpoints
+struct StreamQuery {
+    stream: StreamId,
+    /// Number of items, `n`
+    count: i64,
+    /// Oldest items first, `r=o`
+    oldest_first: bool,
+    /// Streams whose items are excluded, `xt`
+    exclude: Vec<StreamId>,
+    /// Streams whose items are the only ones included, `it`
+    include: Vec<StreamId>,
+    /// Only items published since the UNIX timestamp, `ot`
+    newer_than: Option<i64>,
+    /// Only items published before the UNIX timestamp, `nt`
+    older_than: Option<i64>,
+    /// Continuation returned with the previous items, `c`
+    continuation: Option<Cursor>,
+}
+
+impl StreamQuery {
+    fn from_params(params: &Params, stream: Option<&str>) -> Result<Self> {
+        let parse_stream = |stream: &str| {
+            StreamId::parse(stream)
+                .ok_or_else(|| Error::BadRequest(format!("Invalid stream {}", stream)))
+        };
+        let parse_number = |key: &str| {
+            params
+                .get(key)
+                .map(|value| {
+                    value
+                        .parse::<i64>()
+                        .map_err(|_| Error::BadRequest(format!("Invalid {}", key)))
+                })
+                .transpose()
+        };
+
+        let stream = stream
+            .or_else(|| params.get("s"))
+            .ok_or_else(|| Error::BadRequest("Missing stream".to_string()))?;
+        let continuation = params
+            .get("c")
+            .map(|c| Cursor::try_from(c.to_string()).map_err(Error::BadRequest))
+            .transpose()?;
+
+        Ok(Self {
+            stream: parse_stream(stream)?,
+            count: parse_number("n")?.unwrap_or(20).clamp(1, MAX_ITEMS),
+            oldest_first: params.get("r") == Some("o"),
+            exclude: params
+                .get_all("xt")
+                .map(parse_stream)
+                .collect::<Result<_>>()?,
+            include: params
+                .get_all("it")
+                .map(parse_stream)
+                .collect::<Result<_>>()?,
+            newer_than: parse_number("ot")?,
+            older_than: parse_number("nt")?,
+            continuation,
+        })
+    }
+
+    async fn fetch(&self, pool: &sqlx::PgPool, owner: i32) -> Result<Page<TimelineItem>> {
+        let mut params = SqlParams::new();
+        let user = format!("{}::integer", params.push(owner));
+
+        let mut conditions = vec![self.stream.condition_sql(&user, &mut params)];
+        for stream in &self.exclude {
+            conditions.push(format!(
+                "NOT ({})",
+                stream.condition_sql(&user, &mut params)
+            ));
+        }
+        for stream in &self.include {
+            conditions.push(stream.condition_sql(&user, &mut params));
+        }
+        if let Some(newer_than) = self.newer_than {
+            conditions.push(format!(
+                "{} >= to_timestamp({}::bigint)",
+                PUBLISHED_AT_SQL,
+                params.push(newer_than)
+            ));
+        }
+        if let Some(older_than) = self.older_than {
+            conditions.push(format!(
+                "{} < to_timestamp({}::bigint)",
+                PUBLISHED_AT_SQL,
+                params.push(older_than)
+            ));
+        }
+
+        let order = RowOrder::new("feed_item.id", !self.oldest_first)
+            .key(PUBLISHED_AT_SQL, SortKeyType::Timestamp);
+        let pagination = CursorPagination {
+            after: self.continuation.clone(),
+            before: None,
+            limit: self.count,
+        };
+        pagination.validate_for(&order)?;
+
+        Ok(TimelineItem::fetch_page(
+            pool,
+            owner,
+            &conditions.join(" AND "),
+            &order,
+            &pagination,
+            params,
+        )
+        .await?)
+    }
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Link {
+    pub href: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Content {
+    pub direction: String,
+    pub content: String,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Origin {
+    pub stream_id: String,
+    pub title: String,
+    pub html_url: String,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Item {
+    /// Long form of the item's id
+    pub id: String,
+    pub crawl_time_msec: String,
+    pub timestamp_usec: String,
+    /// UNIX timestamps, in seconds
+    pub published: i64,
+    pub updated: i64,
+    pub title: String,
+    pub canonical: Vec<Link>,
+    pub alternate: Vec<Link>,
+    pub summary: Content,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// States of the item for the user, such as read and starred
+    pub categories: Vec<String>,
+    pub origin: Origin,
+}
+
+impl Item {
+    fn new(item: TimelineItem) -> Self {
+        let published = item.item.published_at.unwrap_or(item.item.created_at);
+        let links: Vec<Link> = item
+            .item
+            .link
+            .iter()
+            .map(|link| Link {
+                href: link.clone(),
+                type_: None,
+            })
+            .collect();
+        let alternate = item
+            .item
+            .link
+            .iter()
+            .map(|link| Link {
+                href: link.clone(),
+                type_: Some("text/html".to_string()),
+            })
+            .collect();
+
+        let mut categories = vec![READING_LIST.to_string()];
+        if item.read {
+            categories.push(READ.to_string());
+        }
+        if item.starred {
+            categories.push(STARRED.to_string());
+        }
+
+        Self {
+            id: long_item_id(item.item.id),
+            crawl_time_msec: item.item.created_at.timestamp_millis().to_string(),
+            timestamp_usec: published.timestamp_micros().to_string(),
+            published: published.timestamp(),
+            updated: item.item.updated_at.timestamp(),
+            title: item.item.title,
+            canonical: links,
+            alternate,
+            summary: Content {
+                direction: "ltr".to_string(),
+                content: item
+                    .item
+                    .content
+                    .or(item.item.description)
+                    .unwrap_or_default(),
+            },
+            author: item.item.author,
+            categories,
+            origin: Origin {
+                stream_id: feed_id(item.item.feed_id),
+                title: item.feed_title,
+                html_url: item
+                    .feed_domain
+                    .map(|domain| format!("https://{}", domain))
+                    .unwrap_or_default(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct StreamContents {
+    pub id: String,
+    /// UNIX timestamp of the response, in seconds
+    pub updated: i64,
+    pub items: Vec<Item>,
+    /// Pass as `c` to get the next items, if there are any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continuation: Option<String>,
+}
+
+/// Builds the contents of a stream from a page of items
+fn stream_contents_from_page(id: String, page: Page<TimelineItem>) -> StreamContents {
+    StreamContents {
+        id,
+        updated: chrono::Utc::now().timestamp(),
+        items: page.items.into_iter().map(Item::new).collect(),
+        continuation: page.next_cursor.map(String::from),
+    }
+}
+
+/// Get the items of a stream
+///
+/// Returns the items of the stream `s`, newest first unless `r=o`, `n` at a time.
+#[utoipa::path(
+    get,
+    path = "/reader/api/0/stream/contents",
+    tag = "greader",
+    params(
+        ("s" = String, Query, description = "Stream id, such as `feed/1`, `user/-/label/News` or `user/-/state/com.google/reading-list`"),
+        ("n" = Option<i64>, Query, description = "Number of items, at most 100"),
+        ("r" = Option<String>, Query, description = "`o` for oldest items first"),
+        ("xt" = Option<String>, Query, description = "Stream whose items are excluded, such as `user/-/state/com.google/read`"),
+        ("it" = Option<String>, Query, description = "Stream whose items are the only ones included"),
+        ("ot" = Option<i64>, Query, description = "Only items published since the UNIX timestamp"),
+        ("nt" = Option<i64>, Query, description = "Only items published before the UNIX timestamp"),
+        ("c" = Option<String>, Query, description = "Continuation returned with the previous items")
+    ),
+    responses(
+        (status = 200, description = "Items of the stream", body = StreamContents),
+        (status = 400, description = "Invalid stream or parameters"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("GoogleLogin" = [])
+    )
+)]
+pub async fn stream_contents(
+    GReaderUser(user): GReaderUser,
+    State(state): State<ApiContext>,
+    Query(query): Query<Vec<(String, String)>>,
+) -> Result<Json<StreamContents>> {
+    let params = Params::new(query, vec![]);
+    let query = StreamQuery::from_params(&params, None)?;
+    let page = query.fetch(&state.pool, user.id).await?;
+
+    let id = params.get("s").unwrap_or_default().to_string();
+    Ok(Json(stream_contents_from_page(id, page)))
+}
+
+/// Same as [stream_contents], with the stream id in the path
+pub async fn stream_contents_by_path(
+    GReaderUser(user): GReaderUser,
+    State(state): State<ApiContext>,
+    Path(stream): Path<String>,
+    Query(query): Query<Vec<(String, String)>>,
+) -> Result<Json<StreamContents>> {
+    let params = Params::new(query, vec![]);
+    let query = StreamQuery::from_params(&params, Some(&stream))?;
+    let page = query.fetch(&state.pool, user.id).await?;
+
+    Ok(Json(stream_contents_from_page(stream, page)))
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemRef {
+    /// Short form of the item's id
+    pub id: String,
+    pub direct_stream_ids: Vec<String>,
+    pub timestamp_usec: String,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemRefs {
+    pub item_refs: Vec<ItemRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continuation: Option<String>,
+}
+
+/// Get the item ids of a stream
+///
+/// Takes the same parameters as the stream contents, returning only the ids of the items.
+/// Clients get the contents of the ones they don't have with `POST /stream/items/contents`.
+#[utoipa::path(
+    get,
+    path = "/reader/api/0/stream/items/ids",
+    tag = "greader",
+    params(
+        ("s" = String, Query, description = "Stream id"),
+        ("n" = Option<i64>, Query, description = "Number of items, at most 100"),
+        ("r" = Option<String>, Query, description = "`o` for oldest items first"),
+        ("xt" = Option<String>, Query, description = "Stream whose items are excluded"),
+        ("it" = Option<String>, Query, description = "Stream whose items are the only ones included"),
+        ("ot" = Option<i64>, Query, description = "Only items published since the UNIX timestamp"),
+        ("nt" = Option<i64>, Query, description = "Only items published before the UNIX timestamp"),
+        ("c" = Option<String>, Query, description = "Continuation returned with the previous ids")
+    ),
+    responses(
+        (status = 200, description = "Ids of the items of the stream", body = ItemRefs),
+        (status = 400, description = "Invalid stream or parameters"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("GoogleLogin" = [])
+    )
+)]
+pub async fn stream_item_ids(
+    GReaderUser(user): GReaderUser,
+    State(state): State<ApiContext>,
+    Query(query): Query<Vec<(String, String)>>,
+) -> Result<Json<ItemRefs>> {
+    let params = Params::new(query, vec![]);
+    let page = StreamQuery::from_params(&params, None)?
+        .fetch(&state.pool, user.id)
+        .await?;
+
+    Ok(Json(ItemRefs {
+        item_refs: page
+            .items
+            .iter()
+            .map(|item| ItemRef {
+                id: item.item.id.to_string(),
+                direct_stream_ids: vec![feed_id(item.item.feed_id)],
+                timestamp_usec: item
+                    .item
+                    .published_at
+                    .unwrap_or(item.item.created_at)
+                    .timestamp_micros()
+                    .to_string(),
+            })
+            .collect(),
+        continuation: page.next_cursor.map(String::from),
+    }))
+}
+
+/// Get the contents of items
+///
+/// Returns the items whose ids, in either form, are given as `i` parameters, among the feeds
+/// the user is subscribed to.
+#[utoipa::path(
+    post,
+    path = "/reader/api/0/stream/items/contents",
+    tag = "greader",
+    request_body(content = String, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Contents of the items", body = StreamContents),
+        (status = 400, description = "Invalid item id"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("GoogleLogin" = [])
+    )
+)]
+pub async fn item_contents(
+    GReaderUser(user): GReaderUser,
+    State(state): State<ApiContext>,
+    Query(query): Query<Vec<(String, String)>>,
+    Form(form): Form<Vec<(String, String)>>,
+) -> Result<Json<StreamContents>> {
+    let params = Params::new(query, form);
+    let ids = parse_item_ids(&params)?;
+    let ids: Vec<String> = ids
+        .iter()
+        .take(MAX_ITEMS as usize)
+        .map(i64::to_string)
+        .collect();
+
+    let mut sql_params = SqlParams::new();
+    let owner = format!("{}::integer", sql_params.push(user.id));
+    let condition = format!(
+        "feed_item.id = ANY({}::bigint[]) AND {}",
+        sql_params.push(format!("{{{}}}", ids.join(","))),
+        subscribed_feeds_sql(&owner)
+    );
+    let order = RowOrder::new("feed_item.id", true).key(PUBLISHED_AT_SQL, SortKeyType::Timestamp);
+    let pagination = CursorPagination {
+        after: None,
+        before: None,
+        limit: MAX_ITEMS,
+    };
+    let page = TimelineItem::fetch_page(
+        &state.pool,
+        user.id,
+        &condition,
+        &order,
+        &pagination,
+        sql_params,
+    )
+    .await?;
+
+    Ok(Json(stream_contents_from_page(
+        READING_LIST.to_string(),
+        page,
+    )))
+}
+
+/// Parses the ids of the items of the `i` parameters
+pub fn parse_item_ids(params: &Params) -> Result<Vec<i64>> {
+    params
+        .get_all("i")
+        .map(|id| {
+            parse_item_id(id).ok_or_else(|| Error::BadRequest(format!("Invalid item {}", id)))
+        })
+        .collect()
+}
@@ -0,0 +1,311 @@
+use axum::Form;
+
+use super::auth::GReaderUser;
+use super::ids::{feed_id, label_id, StreamId};
+use super::Params;
+use crate::http::common::*;
+use crate::sql::{Feed, UserFeed, UserTag};
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Category {
+    pub id: String,
+    pub label: String,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscription {
+    /// Stream id of the feed, `feed/<id>`
+    pub id: String,
+    pub title: String,
+    /// Labels of the user's tags containing the feed
+    pub categories: Vec<Category>,
+    /// URL of the feed
+    pub url: String,
+    /// URL of the feed's website
+    pub html_url: String,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SubscriptionList {
+    pub subscriptions: Vec<Subscription>,
+}
+
+/// List subscriptions
+#[utoipa::path(
+    get,
+    path = "/reader/api/0/subscription/list",
+    tag = "greader",
+    responses(
+        (status = 200, description = "The user's subscriptions", body = SubscriptionList),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("GoogleLogin" = [])
+    )
+)]
+pub async fn list_subscriptions(
+    GReaderUser(user): GReaderUser,
+    State(state): State<ApiContext>,
+) -> Result<Json<SubscriptionList>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT user_feed.feed, user_feed.title, feed.link, feed.domain,
+            ARRAY(
+                SELECT user_tag.title FROM user_tag
+                WHERE user_tag.owner = $1 AND ROW('feed', user_feed.feed)::tag_child = ANY(user_tag.children)
+                ORDER BY lower(user_tag.title)
+            ) AS "labels!"
+        FROM user_feed
+        JOIN feed ON feed.id = user_feed.feed
+        WHERE user_feed.owner = $1
+        ORDER BY lower(user_feed.title), user_feed.id
+        "#,
+        user.id
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let subscriptions = rows
+        .into_iter()
+        .map(|row| Subscription {
+            id: feed_id(row.feed),
+            title: row.title,
+            categories: row
+                .labels
+                .into_iter()
+                .map(|label| Category {
+                    id: label_id(&label),
+                    label,
+                })
+                .collect(),
+            html_url: row
+                .domain
+                .map(|domain| format!("https://{}", domain))
+                .unwrap_or_else(|| row.link.clone()),
+            url: row.link,
+        })
+        .collect();
+    Ok(Json(SubscriptionList { subscriptions }))
+}
+
+/// Returns the user's subscription to the feed of the stream id, which is either `feed/<id>`
+/// or `feed/<url>`
+async fn find_subscription(
+    pool: &sqlx::PgPool,
+    owner: i32,
+    stream: &str,
+) -> Result<Option<UserFeed>> {
+    let feed = match StreamId::parse(stream) {
+        Some(StreamId::Feed(feed)) => feed,
+        _ => {
+            let link = stream.strip_prefix("feed/").unwrap_or(stream).to_string();
+            match Feed::get_by_link(pool, &link).await? {
+                Some(feed) => feed.id,
+                None => return Ok(None),
+            }
+        }
+    };
+    Ok(UserFeed::get_by_owner_and_feed(pool, owner, feed).await?)
+}
+
+/// Returns the title of the tag of a label stream id
+fn label_title(stream: &str) -> Result<String> {
+    match StreamId::parse(stream) {
+        Some(StreamId::Label(title)) => Ok(title),
+        _ => Err(Error::BadRequest(format!("Invalid label {}", stream))),
+    }
+}
+
+/// Edit subscriptions
+///
+/// Depending on `ac`, subscribes to the feeds of the `s` parameters (`feed/<url>`),
+/// unsubscribes from them, or edits them by renaming them to `t`, adding them to the label
+/// `a` and removing them from the label `r`.
+#[utoipa::path(
+    post,
+    path = "/reader/api/0/subscription/edit",
+    tag = "greader",
+    request_body(content = String, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Subscriptions edited", body = String, content_type = "text/plain"),
+        (status = 400, description = "Invalid action, feed or label"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Not subscribed to the feed"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("GoogleLogin" = [])
+    )
+)]
+pub async fn edit_subscription(
+    GReaderUser(user): GReaderUser,
+    State(state): State<ApiContext>,
+    Query(query): Query<Vec<(String, String)>>,
+    Form(form): Form<Vec<(String, String)>>,
+) -> Result<&'static str> {
+    let params = Params::new(query, form);
+    let title = params.get("t").filter(|title| !title.is_empty());
+
+    for stream in params.get_all("s") {
+        match params.get("ac") {
+            Some("subscribe") => {
+                let link = stream.strip_prefix("feed/").unwrap_or(stream);
+                let (subscription, _) =
+                    UserFeed::subscribe(&state.pool, user.id, link, title.map(String::from), None)
+                        .await?;
+                if let Some(label) = params.get("a") {
+                    UserTag::add_feeds(
+                        &state.pool,
+                        user.id,
+                        &label_title(label)?,
+                        &[subscription.feed],
+                    )
+                    .await?;
+                }
+            }
+            Some("unsubscribe") => {
+                if let Some(subscription) = find_subscription(&state.pool, user.id, stream).await? {
+                    subscription.delete(&state.pool).await?;
+                }
+            }
+            Some("edit") => {
+                let mut subscription = find_subscription(&state.pool, user.id, stream)
+                    .await?
+                    .ok_or_else(|| Error::NotFound("Not subscribed to the feed".to_string()))?;
+                if let Some(title) = title {
+                    subscription.title = title.to_string();
+                    subscription.updated_at = chrono::Utc::now();
+                    subscription.update(&state.pool).await?;
+                }
+                for label in params.get_all("a") {
+                    UserTag::add_feeds(
+                        &state.pool,
+                        user.id,
+                        &label_title(label)?,
+                        &[subscription.feed],
+                    )
+                    .await?;
+                }
+                for label in params.get_all("r") {
+                    UserTag::remove_feed(
+                        &state.pool,
+                        user.id,
+                        &label_title(label)?,
+                        subscription.feed,
+                    )
+                    .await?;
+                }
+            }
+            _ => return Err(Error::BadRequest("Invalid action".to_string())),
+        }
+    }
+
+    Ok("OK")
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickAddResponse {
+    pub num_results: i32,
+    pub query: String,
+    pub stream_id: String,
+}
+
+/// Subscribe to a feed
+#[utoipa::path(
+    post,
+    path = "/reader/api/0/subscription/quickadd",
+    tag = "greader",
+    request_body(content = String, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Subscribed to the feed", body = QuickAddResponse),
+        (status = 400, description = "Invalid URL or the feed could not be fetched"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("GoogleLogin" = [])
+    )
+)]
+pub async fn quick_add(
+    GReaderUser(user): GReaderUser,
+    State(state): State<ApiContext>,
+    Query(query): Query<Vec<(String, String)>>,
+    Form(form): Form<Vec<(String, String)>>,
+) -> Result<Json<QuickAddResponse>> {
+    let params = Params::new(query, form);
+    let link = params
+        .get("quickadd")
+        .ok_or_else(|| Error::BadRequest("Missing quickadd".to_string()))?;
+    let link = link.strip_prefix("feed/").unwrap_or(link);
+
+    let (subscription, _) = UserFeed::subscribe(&state.pool, user.id, link, None, None).await?;
+    Ok(Json(QuickAddResponse {
+        num_results: 1,
+        query: link.to_string(),
+        stream_id: feed_id(subscription.feed),
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    fn edit(body: &str) -> http::Request<String> {
+        http::Request::builder()
+            .method(http::Method::POST)
+            .uri("/greader/reader/api/0/subscription/edit")
+            .header(
+                http::header::CONTENT_TYPE,
+                "application/x-www-form-urlencoded",
+            )
+            .body(body.to_string())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn edits_subscriptions() {
+        let ctx = TestContext::new().await;
+        insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        insert_subscription(&ctx.pool, 1, feed).await;
+        insert_tag(&ctx.pool, 1, "News", &[feed]).await;
+
+        let list = || {
+            http::Request::builder()
+                .uri("/greader/reader/api/0/subscription/list?output=json")
+                .body(String::new())
+                .unwrap()
+        };
+        let list: SubscriptionList = ctx.decode(ctx.req(list()).await).await;
+        assert_eq!(list.subscriptions.len(), 1);
+        assert_eq!(list.subscriptions[0].id, format!("feed/{}", feed));
+        assert_eq!(list.subscriptions[0].url, "https://example.com/feed");
+        assert_eq!(list.subscriptions[0].categories[0].id, "user/-/label/News");
+
+        let body = format!(
+            "ac=edit&s=feed%2F{}&t=Renamed&a=user%2F-%2Flabel%2FTech&r=user%2F-%2Flabel%2FNews",
+            feed
+        );
+        assert_eq!(ctx.req(edit(&body)).await.status(), 200);
+        let list: SubscriptionList = ctx.decode(ctx.req(list()).await).await;
+        assert_eq!(list.subscriptions[0].title, "Renamed");
+        let labels: Vec<&str> = list.subscriptions[0]
+            .categories
+            .iter()
+            .map(|category| category.label.as_str())
+            .collect();
+        assert_eq!(labels, vec!["Tech"]);
+
+        let body = "ac=unsubscribe&s=feed%2Fhttps%3A%2F%2Fexample.com%2Ffeed";
+        assert_eq!(ctx.req(edit(body)).await.status(), 200);
+        let list: SubscriptionList = ctx.decode(ctx.req(list()).await).await;
+        assert!(list.subscriptions.is_empty());
+
+        assert_eq!(ctx.req(edit("ac=rename&s=feed%2F1")).await.status(), 403);
+    }
+}
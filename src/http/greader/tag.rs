@@ -0,0 +1,56 @@
+use super::auth::GReaderUser;
+use super::ids::{label_id, STARRED};
+use crate::http::common::*;
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Tag {
+    pub id: String,
+    /// `folder` for labels, absent for states
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TagList {
+    pub tags: Vec<Tag>,
+}
+
+/// List tags
+///
+/// Lists the starred state and a label for each of the user's tags.
+#[utoipa::path(
+    get,
+    path = "/reader/api/0/tag/list",
+    tag = "greader",
+    responses(
+        (status = 200, description = "The user's tags", body = TagList),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("GoogleLogin" = [])
+    )
+)]
+pub async fn list_tags(
+    GReaderUser(user): GReaderUser,
+    State(state): State<ApiContext>,
+) -> Result<Json<TagList>> {
+    let titles = sqlx::query_scalar!(
+        "SELECT title FROM user_tag WHERE owner = $1 ORDER BY lower(title), id",
+        user.id
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let starred = Tag {
+        id: STARRED.to_string(),
+        type_: None,
+    };
+    let labels = titles.iter().map(|title| Tag {
+        id: label_id(title),
+        type_: Some("folder".to_string()),
+    });
+    Ok(Json(TagList {
+        tags: std::iter::once(starred).chain(labels).collect(),
+    }))
+}
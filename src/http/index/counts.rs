@@ -0,0 +1,178 @@
+use crate::http::common::*;
+use crate::query::{parse_query, SqlParams};
+use crate::sql::subscribed_feed_ids_sql;
+
+/// Number of items of an index, tag or feed, and how many of them haven't been read
+#[derive(Serialize, Deserialize, Debug, PartialEq, utoipa::ToSchema)]
+pub struct ItemCount {
+    pub id: i32,
+    pub unread: i64,
+    pub total: i64,
+}
+
+/// Item counts of everything the user can open as a timeline
+#[derive(Serialize, Deserialize, Debug, Default, utoipa::ToSchema)]
+pub struct ItemCounts {
+    pub indexes: Vec<ItemCount>,
+    pub tags: Vec<ItemCount>,
+    pub feeds: Vec<ItemCount>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ItemCountRow {
+    kind: String,
+    id: i32,
+    unread: i64,
+    total: i64,
+}
+
+/// Get item counts
+///
+/// Returns how many items, and how many unread items, each of the user's indexes and tags and
+/// each feed they are subscribed to have, for the badges of a sidebar. Indexes count the items
+/// of their timeline. Indexes whose query can't be parsed are left out.
+#[utoipa::path(
+    get,
+    path = "/counts",
+    tag = "index",
+    responses(
+        (status = 200, description = "Item counts", body = ItemCounts),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn get_counts(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+) -> Result<Json<ItemCounts>> {
+    let indexes = sqlx::query!(
+        "SELECT id, query FROM user_index WHERE owner = $1 ORDER BY id",
+        user.id
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let mut params = SqlParams::new();
+    let owner = format!("{}::integer", params.push(user.id));
+
+    // Items are counted once per feed, and the counts of the feeds of a tag are added up
+    let mut counts = vec![
+        "SELECT 'feed', id, unread, total FROM feed_counts".to_string(),
+        format!(
+            "SELECT 'tag', user_tag.id, \
+            COALESCE(SUM(feed_counts.unread), 0)::bigint, \
+            COALESCE(SUM(feed_counts.total), 0)::bigint \
+            FROM user_tag \
+            LEFT JOIN LATERAL (\
+                SELECT DISTINCT (child).id FROM unnest(user_tag.children) AS child \
+                WHERE (child).type = 'feed'\
+            ) AS child ON TRUE \
+            LEFT JOIN feed_counts ON feed_counts.id = child.id \
+            WHERE user_tag.owner = {} \
+            GROUP BY user_tag.id",
+            owner
+        ),
+    ];
+    for index in indexes {
+        let Ok(query) = parse_query(&index.query) else {
+            continue;
+        };
+        counts.push(format!(
+            "SELECT 'index', {}::integer, \
+            COUNT(*) FILTER (WHERE user_item_state.read IS NOT TRUE), COUNT(*) \
+            FROM feed_item \
+            LEFT JOIN user_item_state ON user_item_state.item = feed_item.id \
+            AND user_item_state.owner = {} \
+            WHERE feed_item.feed_id IN (SELECT id FROM subscribed) AND {}",
+            params.push(index.id),
+            owner,
+            query.to_sql_with_params(user.id, &mut params)
+        ));
+    }
+
+    let sql = format!(
+        "WITH subscribed (id) AS ({}), \
+        feed_counts AS (\
+            SELECT subscribed.id, COUNT(feed_item.id) AS total, \
+            COUNT(feed_item.id) FILTER (WHERE user_item_state.read IS NOT TRUE) AS unread \
+            FROM subscribed \
+            LEFT JOIN feed_item ON feed_item.feed_id = subscribed.id \
+            LEFT JOIN user_item_state ON user_item_state.item = feed_item.id \
+            AND user_item_state.owner = {} \
+            GROUP BY subscribed.id\
+        ) \
+        SELECT * FROM ({}) AS counts (kind, id, unread, total)",
+        subscribed_feed_ids_sql(&owner),
+        owner,
+        counts.join(" UNION ALL ")
+    );
+    let mut query = sqlx::query_as::<_, ItemCountRow>(&sql);
+    for param in params.into_inner() {
+        query = query.bind(param);
+    }
+
+    let mut counts = ItemCounts::default();
+    for row in query.fetch_all(&state.pool).await? {
+        let count = ItemCount {
+            id: row.id,
+            unread: row.unread,
+            total: row.total,
+        };
+        match row.kind.as_str() {
+            "index" => counts.indexes.push(count),
+            "tag" => counts.tags.push(count),
+            _ => counts.feeds.push(count),
+        }
+    }
+    counts.feeds.sort_by_key(|count| count.id);
+    counts.tags.sort_by_key(|count| count.id);
+    Ok(Json(counts))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sql::{SortOrder, UserItemState};
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn counts_unread_items() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let subscribed = insert_feed(&ctx.pool, "https://example.com/feed", "Subscribed").await;
+        let tagged = insert_feed(&ctx.pool, "https://tagged.com/feed", "Tagged").await;
+        let other = insert_feed(&ctx.pool, "https://other.com/feed", "Other").await;
+        insert_subscription(&ctx.pool, user.id, subscribed).await;
+        let tag = insert_tag(&ctx.pool, user.id, "News", &[tagged]).await;
+
+        let read = insert_item(&ctx.pool, subscribed, "Rust news", "").await;
+        insert_item(&ctx.pool, subscribed, "Gardening", "").await;
+        insert_item(&ctx.pool, tagged, "More rust", "").await;
+        insert_item(&ctx.pool, other, "Rust elsewhere", "").await;
+        UserItemState::set_read(&ctx.pool, user.id, &[read], true)
+            .await
+            .unwrap();
+
+        let index = insert_index(&ctx.pool, user.id, "rust", SortOrder::AsIs).await;
+
+        let request = http::Request::builder()
+            .uri("/index/counts")
+            .body(String::new())
+            .unwrap();
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 200);
+
+        let counts: ItemCounts = ctx.decode(response).await;
+        let count = |id, unread, total| ItemCount { id, unread, total };
+        assert_eq!(counts.indexes, vec![count(index.id, 1, 2)]);
+        assert_eq!(counts.tags, vec![count(tag, 1, 1)]);
+        assert_eq!(
+            counts.feeds,
+            vec![count(subscribed, 1, 2), count(tagged, 1, 1)]
+        );
+    }
+}
@@ -1,17 +1,25 @@
 use crate::http::common::*;
-use crate::sql::{Icon, InsertUserIndex, SortOrder, UserIndex};
+use crate::query::{parse_query, QueryError};
+use crate::sql::{
+    generate_slug, Icon, InsertUserIndex, SortOrder, UserIndex, Visibility, SLUG_REGEX,
+};
 use bind_macros::IntoRequest;
 
 #[derive(Deserialize, Serialize, Validate, IntoRequest, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateIndexRequest {
-    #[validate(custom(function = "crate::query::validate_query"))]
     query: String,
     sort: SortOrder,
     #[validate(length(min = 1, max = 1024))]
     title: String,
     description: Option<String>,
     icon: Icon,
+    /// Who can see the index, private by default
+    #[serde(default)]
+    visibility: Visibility,
+    /// Identifier of the index in its public URL, generated from the title by default
+    #[validate(length(min = 3, max = 64), regex(path = *SLUG_REGEX))]
+    slug: Option<String>,
 }
 
 /// Create a new index
@@ -22,8 +30,10 @@ pub struct CreateIndexRequest {
     request_body = CreateIndexRequest,
     responses(
         (status = 200, description = "Index created successfully", body = UserIndex),
-        (status = 400, description = "Invalid index parameters"),
+        (status = 400, description = "Invalid index parameters or query", body = QueryError),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Shared index filtering by read or starred state"),
+        (status = 409, description = "Slug already in use"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -36,25 +46,38 @@ pub async fn create_index(
     Json(body): Json<CreateIndexRequest>,
 ) -> Result<Json<UserIndex>> {
     body.validate()?;
+    let query = parse_query(&body.query)?;
+    if body.visibility != Visibility::Private && query.uses_item_state() {
+        return Err(Error::BadRequest(
+            "Shared indexes can't filter by read or starred state".to_string(),
+        ));
+    }
+    let slug = body.slug.unwrap_or_else(|| generate_slug(&body.title));
+    let conflict = format!("Slug {} is already in use", slug);
 
-    let sort = body.sort.to_string();
     let query = InsertUserIndex {
         owner: user.id,
         query: body.query,
-        sort,
+        sort: body.sort,
         title: body.title,
         description: body.description,
         icon: body.icon,
+        visibility: body.visibility,
+        slug,
     }
     .insert(&state.pool)
-    .await?;
+    .await
+    .map_err(Error::conflict_on_unique(conflict))?;
 
     Ok(Json(query))
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{sql::Icon, tests::TestContext};
+    use crate::{
+        sql::Icon,
+        tests::{fixtures::insert_user, TestContext},
+    };
 
     use super::*;
 
@@ -69,6 +92,8 @@ mod test {
             title: "Hello World".to_string(),
             description: None,
             icon: Icon::get_random_icon(),
+            visibility: Visibility::Private,
+            slug: None,
         };
 
         let response = ctx
@@ -76,4 +101,28 @@ mod test {
             .await;
         assert_eq!(response.status(), http::StatusCode::OK);
     }
+
+    #[tokio::test]
+    async fn rejects_taken_slugs() {
+        let ctx = TestContext::new().await;
+        insert_user(&ctx.pool).await;
+        let create_index = || CreateIndexRequest {
+            query: "rust".to_string(),
+            sort: SortOrder::AsIs,
+            title: "Rust".to_string(),
+            description: None,
+            icon: Icon::get_random_icon(),
+            visibility: Visibility::Public,
+            slug: Some("rust".to_string()),
+        };
+
+        let response = ctx
+            .req(create_index().into_request(http::Method::PUT, "/index"))
+            .await;
+        assert_eq!(response.status(), http::StatusCode::OK);
+        let response = ctx
+            .req(create_index().into_request(http::Method::PUT, "/index"))
+            .await;
+        assert_eq!(response.status(), http::StatusCode::FORBIDDEN);
+    }
 }
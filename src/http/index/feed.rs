@@ -0,0 +1,393 @@
+use axum::http::{header, HeaderMap, HeaderValue};
+use axum::response::Response;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use utoipa::IntoParams;
+
+use crate::feed::publish::PublishedFeed;
+use crate::feed::FeedFormat;
+use crate::http::common::*;
+use crate::query::{parse_query, SqlParams};
+use crate::sql::{subscribed_feeds_sql, CursorPagination, TimelineItem, UserIndex, UserIndexFeed};
+
+/// Number of the most recent items of the index included in its feed
+const FEED_ITEM_LIMIT: i64 = 50;
+/// How long feed readers may cache the feed before revalidating it
+const FEED_MAX_AGE: &str = "private, max-age=300";
+
+/// Secret token of an index's feed
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct FeedToken {
+    /// Token created with `POST /index/{id}/feed-token`
+    pub token: String,
+}
+
+/// Create a feed token
+///
+/// Creates the secret token which allows reading the index as an RSS, Atom or JSON feed
+/// without authentication, from `/index/{id}/feed.rss?token=...`, `/index/{id}/feed.atom` or
+/// `/index/{id}/feed.json`. If the index already has a token it is replaced, so the URLs shared
+/// with the previous one stop working.
+#[utoipa::path(
+    post,
+    path = "/{id}/feed-token",
+    tag = "index",
+    params(
+        ("id" = i32, Path, description = "Index ID")
+    ),
+    responses(
+        (status = 200, description = "Token of the index's feed", body = UserIndexFeed),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the owner of the index"),
+        (status = 404, description = "Index not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn create_feed_token(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Path(id): Path<i32>,
+) -> Result<Json<UserIndexFeed>> {
+    let index = UserIndex::get(&state.pool, id).await?;
+    if index.owner != user.id {
+        return Err(Error::NotOwner);
+    }
+
+    let feed = UserIndexFeed::rotate(&state.pool, index.id).await?;
+    Ok(Json(feed))
+}
+
+/// Revoke a feed token
+///
+/// Deletes the token of the index's feed, so it can't be read without authentication anymore.
+#[utoipa::path(
+    delete,
+    path = "/{id}/feed-token",
+    tag = "index",
+    params(
+        ("id" = i32, Path, description = "Index ID")
+    ),
+    responses(
+        (status = 204, description = "Token revoked"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the owner of the index"),
+        (status = 404, description = "Index or token not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn revoke_feed_token(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse> {
+    let index = UserIndex::get(&state.pool, id).await?;
+    if index.owner != user.id {
+        return Err(Error::NotOwner);
+    }
+
+    if !UserIndexFeed::revoke(&state.pool, index.id).await? {
+        return Err(Error::NotFound("Index has no feed token".to_string()));
+    }
+    Ok(http::StatusCode::NO_CONTENT)
+}
+
+/// Get an index as an RSS feed
+///
+/// Returns the most recent items of the index as an RSS 2.0 feed, for feed readers and other
+/// tools which can't authenticate.
+#[utoipa::path(
+    get,
+    path = "/{id}/feed.rss",
+    tag = "index",
+    params(
+        ("id" = i32, Path, description = "Index ID"),
+        FeedToken
+    ),
+    responses(
+        (status = 200, description = "RSS feed of the index", content_type = "application/rss+xml"),
+        (status = 304, description = "Feed hasn't changed"),
+        (status = 404, description = "Index not found or invalid token"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_rss_feed(
+    State(state): State<ApiContext>,
+    Path(id): Path<i32>,
+    Query(token): Query<FeedToken>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    index_feed(&state, id, &token.token, &headers, FeedFormat::Rss).await
+}
+
+/// Get an index as an Atom feed
+///
+/// Returns the most recent items of the index as an Atom feed, for feed readers and other
+/// tools which can't authenticate.
+#[utoipa::path(
+    get,
+    path = "/{id}/feed.atom",
+    tag = "index",
+    params(
+        ("id" = i32, Path, description = "Index ID"),
+        FeedToken
+    ),
+    responses(
+        (status = 200, description = "Atom feed of the index", content_type = "application/atom+xml"),
+        (status = 304, description = "Feed hasn't changed"),
+        (status = 404, description = "Index not found or invalid token"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_atom_feed(
+    State(state): State<ApiContext>,
+    Path(id): Path<i32>,
+    Query(token): Query<FeedToken>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    index_feed(&state, id, &token.token, &headers, FeedFormat::Atom).await
+}
+
+/// Get an index as a JSON feed
+///
+/// Returns the most recent items of the index as a JSON Feed 1.1, for feed readers and other
+/// tools which can't authenticate.
+#[utoipa::path(
+    get,
+    path = "/{id}/feed.json",
+    tag = "index",
+    params(
+        ("id" = i32, Path, description = "Index ID"),
+        FeedToken
+    ),
+    responses(
+        (status = 200, description = "JSON feed of the index", content_type = "application/feed+json"),
+        (status = 304, description = "Feed hasn't changed"),
+        (status = 404, description = "Index not found or invalid token"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_json_feed(
+    State(state): State<ApiContext>,
+    Path(id): Path<i32>,
+    Query(token): Query<FeedToken>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    index_feed(&state, id, &token.token, &headers, FeedFormat::Json).await
+}
+
+/// Writes the most recent items of the index's timeline, in the index's sort order, as a feed
+/// in the format. Responses include an `ETag` and a `Last-Modified` date, so feed readers can
+/// revalidate with `If-None-Match` or `If-Modified-Since` and receive 304 Not Modified if
+/// the timeline hasn't changed
+async fn index_feed(
+    state: &ApiContext,
+    id: i32,
+    token: &str,
+    headers: &HeaderMap,
+    format: FeedFormat,
+) -> Result<Response> {
+    // Don't reveal whether the index exists to requests without a valid token
+    if !UserIndexFeed::is_valid(&state.pool, id, token).await? {
+        return Err(Error::NotFound("Index not found".to_string()));
+    }
+    let index = UserIndex::get(&state.pool, id).await?;
+    let query = parse_query(&index.query)?;
+
+    let mut params = SqlParams::new();
+    let owner = format!("{}::integer", params.push(index.owner));
+    let condition = format!(
+        "{} AND {}",
+        subscribed_feeds_sql(&owner),
+        query.to_sql_with_params(index.owner, &mut params)
+    );
+    let seed = format!("index-{}", index.id);
+    let order = index.sort.row_order(&query, &seed, &mut params);
+    let pagination = CursorPagination {
+        after: None,
+        before: None,
+        limit: FEED_ITEM_LIMIT,
+    };
+    let page = TimelineItem::fetch_page(
+        &state.pool,
+        index.owner,
+        &condition,
+        &order,
+        &pagination,
+        params,
+    )
+    .await?;
+    let items: Vec<_> = page.items.into_iter().map(|item| item.item).collect();
+
+    let updated_at = items
+        .iter()
+        .map(|item| item.updated_at)
+        .fold(index.updated_at, DateTime::max);
+    // Digests are stable across builds, unlike std's hashers, so ETags survive upgrades
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{:?}:{}", format, index.updated_at.to_rfc3339()));
+    for item in &items {
+        hasher.update(format!(":{}@{}", item.id, item.updated_at.to_rfc3339()));
+    }
+    let etag = format!("W/\"{:.32x}\"", hasher.finalize());
+    let last_modified = updated_at.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+    let cache_headers = [
+        (
+            header::ETAG,
+            HeaderValue::from_str(&etag).map_err(anyhow::Error::from)?,
+        ),
+        (
+            header::LAST_MODIFIED,
+            HeaderValue::from_str(&last_modified).map_err(anyhow::Error::from)?,
+        ),
+        (
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(FEED_MAX_AGE),
+        ),
+    ];
+    if is_not_modified(headers, &etag, updated_at) {
+        return Ok((http::StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    let feed = PublishedFeed {
+        title: index.title,
+        description: index.description,
+        link: state
+            .origins
+            .web
+            .join(&format!("index/{}", index.id))
+            .map_err(anyhow::Error::from)?
+            .to_string(),
+        updated_at,
+        items,
+    };
+    let (content_type, body) = match format {
+        FeedFormat::Rss => ("application/rss+xml; charset=utf-8", feed.to_rss()),
+        FeedFormat::Atom => ("application/atom+xml; charset=utf-8", feed.to_atom()),
+        FeedFormat::Json => (
+            "application/feed+json",
+            serde_json::to_string(&feed.to_json()).map_err(anyhow::Error::from)?,
+        ),
+    };
+    Ok((
+        cache_headers,
+        [(header::CONTENT_TYPE, HeaderValue::from_static(content_type))],
+        body,
+    )
+        .into_response())
+}
+
+/// Returns whether the client's copy of the feed is still current. `If-None-Match` takes
+/// precedence over `If-Modified-Since`, which only has a precision of seconds
+fn is_not_modified(headers: &HeaderMap, etag: &str, updated_at: DateTime<Utc>) -> bool {
+    if let Some(value) = headers.get(header::IF_NONE_MATCH) {
+        return value
+            .to_str()
+            .map(|value| value.split(',').any(|tag| tag.trim() == etag))
+            .unwrap_or(false);
+    }
+    headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .map(|since| updated_at.timestamp() <= since.timestamp())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod test {
+    use http_body_util::BodyExt;
+
+    use crate::sql::SortOrder;
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn publishes_index_feeds() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        insert_subscription(&ctx.pool, user.id, feed).await;
+        insert_item(&ctx.pool, feed, "Rust news", "").await;
+        insert_item(&ctx.pool, feed, "Gardening", "").await;
+
+        let index = insert_index(&ctx.pool, user.id, "rust", SortOrder::RecentlyUpdated).await;
+
+        let get = |path: String| {
+            http::Request::builder()
+                .uri(path)
+                .body(String::new())
+                .unwrap()
+        };
+        let response = ctx
+            .req(get(format!("/index/{}/feed.rss?token=guess", index.id)))
+            .await;
+        assert_eq!(response.status(), 404);
+
+        let request = http::Request::builder()
+            .method(http::Method::POST)
+            .uri(format!("/index/{}/feed-token", index.id))
+            .body(String::new())
+            .unwrap();
+        let token = ctx
+            .decode::<UserIndexFeed>(ctx.req(request).await)
+            .await
+            .token;
+
+        let response = ctx
+            .req(get(format!("/index/{}/feed.rss?token={}", index.id, token)))
+            .await;
+        assert_eq!(response.status(), 200);
+        let etag = response.headers()[header::ETAG].clone();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let channel = rss::Channel::read_from(&body[..]).unwrap();
+        assert_eq!(channel.title, "rust");
+        assert_eq!(
+            channel
+                .items
+                .iter()
+                .map(|item| item.title.as_deref().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["Rust news"]
+        );
+
+        // ETags are per format, since the bodies differ
+        for (format, status) in [("atom", 200), ("rss", 304)] {
+            let request = http::Request::builder()
+                .uri(format!(
+                    "/index/{}/feed.{}?token={}",
+                    index.id, format, token
+                ))
+                .header(header::IF_NONE_MATCH, etag.clone())
+                .body(String::new())
+                .unwrap();
+            assert_eq!(ctx.req(request).await.status(), status);
+        }
+
+        let response = ctx
+            .req(get(format!(
+                "/index/{}/feed.json?token={}",
+                index.id, token
+            )))
+            .await;
+        let json: serde_json::Value = ctx.decode(response).await;
+        assert_eq!(json["items"][0]["title"], "Rust news");
+
+        let request = http::Request::builder()
+            .method(http::Method::DELETE)
+            .uri(format!("/index/{}/feed-token", index.id))
+            .body(String::new())
+            .unwrap();
+        assert_eq!(ctx.req(request).await.status(), 204);
+        let response = ctx
+            .req(get(format!("/index/{}/feed.rss?token={}", index.id, token)))
+            .await;
+        assert_eq!(response.status(), 404);
+    }
+}
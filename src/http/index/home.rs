@@ -0,0 +1,140 @@
+use utoipa::IntoParams;
+
+use crate::http::common::*;
+use crate::query::{Query as SearchQuery, SearchExpr, SearchField, SqlParams};
+use crate::sql::{
+    subscribed_feeds_sql, CursorPagination, Page, RowOrder, SortKeyType, TimelineItem,
+    PUBLISHED_AT_SQL,
+};
+
+/// Filters of the home timeline
+#[derive(Deserialize, Debug, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct HomeTimelineFilter {
+    /// Only include items which haven't been read
+    #[serde(default)]
+    pub unread: bool,
+    /// Only include items from the feeds in the tag with the ID
+    pub tag: Option<i32>,
+}
+
+/// Get the home timeline
+///
+/// Returns the items of every feed the user is subscribed to, directly or through one of their
+/// tags, newest first, along with the title and domain of their feed and whether they have
+/// been read. The filters are compiled the same way as the `is:unread` and `tag:` fields of an
+/// index's query.
+#[utoipa::path(
+    get,
+    path = "/me",
+    tag = "index",
+    params(
+        HomeTimelineFilter,
+        CursorPagination
+    ),
+    responses(
+        (status = 200, description = "Items of the home timeline", body = Page<TimelineItem>),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn get_home_timeline(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Query(filter): Query<HomeTimelineFilter>,
+    Query(pagination): Query<CursorPagination>,
+) -> Result<Json<Page<TimelineItem>>> {
+    let mut exprs = vec![];
+    if filter.unread {
+        exprs.push(SearchExpr::IsUnread);
+    }
+    if let Some(tag) = filter.tag {
+        exprs.push(SearchExpr::Field(
+            SearchField::Tag,
+            Box::new(SearchExpr::Word(tag.to_string())),
+        ));
+    }
+    let query = SearchQuery { exprs };
+
+    let mut params = SqlParams::new();
+    let owner = format!("{}::integer", params.push(user.id));
+    let condition = format!(
+        "{} AND {}",
+        subscribed_feeds_sql(&owner),
+        query.to_sql_with_params(user.id, &mut params)
+    );
+
+    let order = RowOrder::new("feed_item.id", true).key(PUBLISHED_AT_SQL, SortKeyType::Timestamp);
+    pagination.validate_for(&order)?;
+
+    let page = TimelineItem::fetch_page(
+        &state.pool,
+        user.id,
+        &condition,
+        &order,
+        &pagination,
+        params,
+    )
+    .await?;
+    Ok(Json(page))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sql::UserItemState;
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    async fn get_timeline(ctx: &TestContext, filter: &str) -> Vec<i64> {
+        let request = http::Request::builder()
+            .uri(format!("/index/me?limit=20{}", filter))
+            .body(String::new())
+            .unwrap();
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 200);
+
+        let page: Page<TimelineItem> = ctx.decode(response).await;
+        page.items.iter().map(|item| item.item.id).collect()
+    }
+
+    #[tokio::test]
+    async fn lists_subscribed_items_newest_first() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let news = insert_feed(&ctx.pool, "https://news.com/feed", "News").await;
+        let blog = insert_feed(&ctx.pool, "https://blog.com/feed", "Blog").await;
+        let other = insert_feed(&ctx.pool, "https://other.com/feed", "Other").await;
+        insert_subscription(&ctx.pool, user.id, news).await;
+        insert_subscription(&ctx.pool, user.id, blog).await;
+        let tag = insert_tag(&ctx.pool, user.id, "Blogs", &[blog]).await;
+
+        let first = insert_item(&ctx.pool, news, "First", "").await;
+        let second = insert_item(&ctx.pool, blog, "Second", "").await;
+        let third = insert_item(&ctx.pool, news, "Third", "").await;
+        insert_item(&ctx.pool, other, "Unsubscribed", "").await;
+        // Feeds are also followed through tags, like in the other timelines
+        let tagged = insert_feed(&ctx.pool, "https://tagged.com/feed", "Tagged").await;
+        insert_tag(&ctx.pool, user.id, "Tagged", &[tagged]).await;
+        let fourth = insert_item(&ctx.pool, tagged, "Fourth", "").await;
+        UserItemState::set_read(&ctx.pool, user.id, &[third], true)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_timeline(&ctx, "").await,
+            vec![fourth, third, second, first]
+        );
+        assert_eq!(
+            get_timeline(&ctx, "&unread=true").await,
+            vec![fourth, second, first]
+        );
+        assert_eq!(
+            get_timeline(&ctx, &format!("&tag={}", tag)).await,
+            vec![second]
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use crate::http::common::*;
+use crate::query::{parse_query, SqlParams};
+use crate::sql::{subscribed_feeds_sql, CursorPagination, Page, TimelineItem, UserIndex};
+
+/// List the items of an index
+///
+/// Runs the index's query over the feeds its owner is subscribed to, directly or through one
+/// of their tags, and returns the matching items in the index's sort order. Each item includes
+/// the title and domain of its feed and whether it has been read.
+#[utoipa::path(
+    get,
+    path = "/{id}/items",
+    tag = "index",
+    params(
+        ("id" = i32, Path, description = "Index ID"),
+        CursorPagination
+    ),
+    responses(
+        (status = 200, description = "Items of the index", body = Page<TimelineItem>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the owner of the index"),
+        (status = 404, description = "Index not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn get_index_items(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Path(id): Path<i32>,
+    Query(pagination): Query<CursorPagination>,
+) -> Result<Json<Page<TimelineItem>>> {
+    let index = UserIndex::get(&state.pool, id).await?;
+    if index.owner != user.id {
+        return Err(Error::NotOwner);
+    }
+    let query = parse_query(&index.query)?;
+
+    let mut params = SqlParams::new();
+    let owner = format!("{}::integer", params.push(index.owner));
+    let condition = format!(
+        "{} AND {}",
+        subscribed_feeds_sql(&owner),
+        query.to_sql_with_params(index.owner, &mut params)
+    );
+    // Shuffle each index differently, but the same way on every page
+    let seed = format!("index-{}", index.id);
+    let order = index.sort.row_order(&query, &seed, &mut params);
+    pagination.validate_for(&order)?;
+
+    let page = TimelineItem::fetch_page(
+        &state.pool,
+        user.id,
+        &condition,
+        &order,
+        &pagination,
+        params,
+    )
+    .await?;
+    Ok(Json(page))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sql::{SortOrder, UserItemState};
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn lists_matching_items_from_subscriptions() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let subscribed = insert_feed(&ctx.pool, "https://example.com/feed", "Subscribed").await;
+        let tagged = insert_feed(&ctx.pool, "https://tagged.com/feed", "Tagged").await;
+        let other = insert_feed(&ctx.pool, "https://other.com/feed", "Other").await;
+        insert_subscription(&ctx.pool, user.id, subscribed).await;
+        insert_tag(&ctx.pool, user.id, "News", &[tagged]).await;
+
+        let subscribed_item = insert_item(&ctx.pool, subscribed, "Rust news", "").await;
+        let tagged_item = insert_item(&ctx.pool, tagged, "More rust", "").await;
+        insert_item(&ctx.pool, subscribed, "Gardening", "").await;
+        insert_item(&ctx.pool, other, "Rust elsewhere", "").await;
+        UserItemState::set_read(&ctx.pool, user.id, &[tagged_item], true)
+            .await
+            .unwrap();
+
+        let index = insert_index(&ctx.pool, user.id, "rust", SortOrder::AsIs).await;
+
+        let request = http::Request::builder()
+            .uri(format!("/index/{}/items", index.id))
+            .body(String::new())
+            .unwrap();
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 200);
+
+        let mut items = ctx.decode::<Page<TimelineItem>>(response).await.items;
+        items.sort_by_key(|item| item.item.id);
+        let items = items
+            .iter()
+            .map(|item| (item.item.id, item.feed_title.as_str(), item.read))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            items,
+            vec![
+                (subscribed_item, "Subscribed", false),
+                (tagged_item, "Tagged", true)
+            ]
+        );
+    }
+}
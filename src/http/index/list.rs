@@ -1,7 +1,7 @@
 use crate::http::common::*;
-use crate::sql::{Icon, UserIndex};
+use crate::sql::{Icon, SortOrder, UserIndex, Visibility};
 
-/// List all indexes
+/// List the user's indexes
 #[utoipa::path(
     get,
     path = "/",
@@ -10,7 +10,7 @@ use crate::sql::{Icon, UserIndex};
         Pagination
     ),
     responses(
-        (status = 200, description = "List of all indexes", body = Vec<UserIndex>),
+        (status = 200, description = "List of the user's indexes", body = Vec<UserIndex>),
         (status = 401, description = "Unauthorized"),
         (status = 500, description = "Internal server error")
     ),
@@ -19,19 +19,56 @@ use crate::sql::{Icon, UserIndex};
     )
 )]
 pub async fn list_indexes(
-    _: AuthUser,
+    user: AuthUser,
     State(state): State<ApiContext>,
     Query(pagination): Query<Pagination>,
 ) -> Result<Json<Vec<UserIndex>>> {
     let values = sqlx::query_as!(
         UserIndex,
         r#"
-        SELECT id, owner, query, sort, title, description, icon as "icon:Icon", created_at, updated_at FROM user_index
+        SELECT id, owner, query, sort as "sort:SortOrder", title, description, icon as "icon:Icon", visibility as "visibility:Visibility", slug, created_at, updated_at FROM user_index
+        WHERE owner = $1
         ORDER BY updated_at DESC
-        LIMIT $1 OFFSET $2
+        LIMIT $2 OFFSET $3
         "#,
+        user.id,
         pagination.limit,
         (pagination.page - 1) * pagination.limit,
     ).fetch_all(&state.pool).await?;
     Ok(Json(values))
 }
+
+#[cfg(test)]
+mod test {
+    use crate::sql::InsertUser;
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn lists_only_own_indexes() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let other = InsertUser {
+            email: "other@example.com".to_string(),
+            username: "other".to_string(),
+            providers: vec![],
+            password_hash: None,
+        }
+        .insert(&ctx.pool)
+        .await
+        .unwrap();
+        let index = insert_index(&ctx.pool, user.id, "rust", SortOrder::AsIs).await;
+        insert_index(&ctx.pool, other.id, "secret", SortOrder::AsIs).await;
+
+        let request = http::Request::builder()
+            .uri("/index?page=1")
+            .body(String::new())
+            .unwrap();
+        let indexes: Vec<UserIndex> = ctx.decode(ctx.req(request).await).await;
+        assert_eq!(
+            indexes.iter().map(|index| index.id).collect::<Vec<_>>(),
+            vec![index.id]
+        );
+    }
+}
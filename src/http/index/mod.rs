@@ -3,9 +3,13 @@ use utoipa_axum::routes;
 
 use super::common::ApiContext;
 
+mod counts;
 mod create;
 mod delete;
+mod feed;
 mod get;
+mod home;
+mod items;
 mod list;
 mod update;
 
@@ -17,4 +21,11 @@ pub fn router() -> OpenApiRouter<ApiContext> {
             delete::delete_index,
             update::update_index
         ))
+        .routes(routes!(counts::get_counts))
+        .routes(routes!(feed::create_feed_token, feed::revoke_feed_token))
+        .routes(routes!(feed::get_rss_feed))
+        .routes(routes!(feed::get_atom_feed))
+        .routes(routes!(feed::get_json_feed))
+        .routes(routes!(home::get_home_timeline))
+        .routes(routes!(items::get_index_items))
 }
@@ -1,16 +1,19 @@
 use crate::http::common::*;
-use crate::sql::{Icon, SortOrder, UserIndex};
+use crate::query::{parse_query, QueryError};
+use crate::sql::{Icon, SortOrder, UserIndex, Visibility, SLUG_REGEX};
 
 #[derive(Deserialize, Validate, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateIndexRequest {
-    #[validate(custom(function = "crate::query::validate_query"))]
     query: Option<String>,
     sort: Option<SortOrder>,
     #[validate(length(min = 1, max = 1024))]
     title: Option<String>,
     description: Option<String>,
     icon: Option<Icon>,
+    visibility: Option<Visibility>,
+    #[validate(length(min = 3, max = 64), regex(path = *SLUG_REGEX))]
+    slug: Option<String>,
 }
 
 /// Update an index
@@ -24,10 +27,11 @@ pub struct UpdateIndexRequest {
     request_body = UpdateIndexRequest,
     responses(
         (status = 200, description = "Index updated successfully", body = UserIndex),
-        (status = 400, description = "Invalid index parameters"),
+        (status = 400, description = "Invalid index parameters or query", body = QueryError),
         (status = 401, description = "Unauthorized"),
-        (status = 403, description = "Not the owner of the index"),
+        (status = 403, description = "Not the owner of the index, or shared index filtering by read or starred state"),
         (status = 404, description = "Index not found"),
+        (status = 409, description = "Slug already in use"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -41,6 +45,9 @@ pub async fn update_index(
     Json(body): Json<UpdateIndexRequest>,
 ) -> Result<Json<UserIndex>> {
     body.validate()?;
+    if let Some(query) = &body.query {
+        parse_query(query)?;
+    }
     let mut index = UserIndex::get(&state.pool, id).await?;
     if index.owner != user.id {
         return Err(Error::NotOwner);
@@ -50,7 +57,7 @@ pub async fn update_index(
         index.query = query;
     }
     if let Some(sort) = body.sort {
-        index.sort = sort.to_string();
+        index.sort = sort;
     }
     if let Some(title) = body.title {
         index.title = title;
@@ -61,9 +68,26 @@ pub async fn update_index(
     if let Some(icon) = body.icon {
         index.icon = icon;
     }
+    if let Some(visibility) = body.visibility {
+        index.visibility = visibility;
+    }
+    if let Some(slug) = body.slug {
+        index.slug = slug;
+    }
+    if index.visibility != Visibility::Private && parse_query(&index.query)?.uses_item_state() {
+        return Err(Error::BadRequest(
+            "Shared indexes can't filter by read or starred state".to_string(),
+        ));
+    }
 
     index.updated_at = chrono::Utc::now();
-    index.update(&state.pool).await?;
+    index
+        .update(&state.pool)
+        .await
+        .map_err(Error::conflict_on_unique(format!(
+            "Slug {} is already in use",
+            index.slug
+        )))?;
 
     Ok(Json(index))
 }
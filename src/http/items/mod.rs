@@ -4,9 +4,16 @@ use utoipa_axum::routes;
 use super::common::ApiContext;
 
 mod get;
+mod state;
 
 pub fn router() -> OpenApiRouter<ApiContext> {
     OpenApiRouter::new()
         .routes(routes!(get::get_item))
         .routes(routes!(get::get_parsed))
+        .routes(routes!(state::mark_read))
+        .routes(routes!(state::mark_unread))
+        .routes(routes!(state::mark_all_read))
+        .routes(routes!(state::star))
+        .routes(routes!(state::unstar))
+        .routes(routes!(state::list_starred))
 }
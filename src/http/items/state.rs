@@ -0,0 +1,392 @@
+use utoipa::ToSchema;
+
+use crate::http::common::*;
+use crate::query::{parse_query, SqlParams};
+use crate::sql::{
+    subscribed_feeds_sql, CursorPagination, Page, RowOrder, SortKeyType, TimelineItem, UserIndex,
+    UserItemState, UserTag, PUBLISHED_AT_SQL,
+};
+
+/// Request to change the state of many items at once
+#[derive(Deserialize, Serialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkItemsRequest {
+    /// Ids of the feed items to update
+    #[validate(length(min = 1, max = 1000))]
+    pub items: Vec<i64>,
+}
+
+/// Result of changing the state of many items
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkItemsResponse {
+    /// Number of items which were updated
+    pub updated: u64,
+}
+
+/// Mark items as read
+///
+/// Marks all of the provided items as read for the current user. Items which were already
+/// read keep the time they were first read at. Ids of items which don't exist are ignored.
+#[utoipa::path(
+    post,
+    path = "/read",
+    tag = "items",
+    request_body = MarkItemsRequest,
+    responses(
+        (status = 200, description = "Items marked as read", body = MarkItemsResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn mark_read(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Json(body): Json<MarkItemsRequest>,
+) -> Result<Json<MarkItemsResponse>> {
+    body.validate()?;
+
+    let updated = UserItemState::set_read(&state.pool, user.id, &body.items, true).await?;
+    Ok(Json(MarkItemsResponse { updated }))
+}
+
+/// Mark items as unread
+///
+/// Marks all of the provided items as unread for the current user. Ids of items which don't
+/// exist are ignored.
+#[utoipa::path(
+    post,
+    path = "/unread",
+    tag = "items",
+    request_body = MarkItemsRequest,
+    responses(
+        (status = 200, description = "Items marked as unread", body = MarkItemsResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn mark_unread(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Json(body): Json<MarkItemsRequest>,
+) -> Result<Json<MarkItemsResponse>> {
+    body.validate()?;
+
+    let updated = UserItemState::set_read(&state.pool, user.id, &body.items, false).await?;
+    Ok(Json(MarkItemsResponse { updated }))
+}
+
+/// Items to mark as read at once, e.g. `{ "feed": 1 }` or `{ "query": "rust" }`
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum MarkReadScope {
+    /// Items of the feed
+    Feed(i32),
+    /// Items of the feeds in the user's tag
+    Tag(i32),
+    /// Items of the user's index
+    Index(i32),
+    /// Items matching the search query
+    Query(String),
+}
+
+/// Request to mark every item of a scope as read
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkAllReadRequest {
+    pub scope: MarkReadScope,
+    /// Only mark items published before the time, such as when the items were loaded, so that
+    /// newer items stay unread
+    pub older_than: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Mark all items of a scope as read
+///
+/// Marks every item of a feed, tag, index or search query as read for the current user in a
+/// single statement, optionally only the ones published before `olderThan`. Only items from
+/// the feeds the user is subscribed to, directly or through one of their tags, are marked.
+#[utoipa::path(
+    post,
+    path = "/mark-read",
+    tag = "items",
+    request_body = MarkAllReadRequest,
+    responses(
+        (status = 200, description = "Number of items which were unread", body = MarkItemsResponse),
+        (status = 400, description = "Invalid query"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the owner of the tag or index"),
+        (status = 404, description = "Tag or index not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn mark_all_read(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Json(body): Json<MarkAllReadRequest>,
+) -> Result<Json<MarkItemsResponse>> {
+    let mut params = SqlParams::new();
+    let owner = format!("{}::integer", params.push(user.id));
+    let mut conditions = vec![subscribed_feeds_sql(&owner)];
+
+    match body.scope {
+        MarkReadScope::Feed(feed) => conditions.push(format!(
+            "feed_item.feed_id = {}::integer",
+            params.push(feed)
+        )),
+        MarkReadScope::Tag(id) => {
+            let tag = UserTag::get(&state.pool, id).await?;
+            if tag.owner != user.id {
+                return Err(Error::NotOwner);
+            }
+            conditions.push(format!(
+                "feed_item.feed_id IN (SELECT (child).id FROM user_tag, \
+                unnest(user_tag.children) AS child \
+                WHERE user_tag.id = {}::integer AND (child).type = 'feed')",
+                params.push(tag.id)
+            ));
+        }
+        MarkReadScope::Index(id) => {
+            let index = UserIndex::get(&state.pool, id).await?;
+            if index.owner != user.id {
+                return Err(Error::NotOwner);
+            }
+            let query = parse_query(&index.query)?;
+            conditions.push(query.to_sql_with_params(user.id, &mut params));
+        }
+        MarkReadScope::Query(query) => {
+            let query = parse_query(&query)?;
+            conditions.push(query.to_sql_with_params(user.id, &mut params));
+        }
+    }
+    if let Some(older_than) = body.older_than {
+        conditions.push(format!(
+            "{} < {}::timestamptz",
+            PUBLISHED_AT_SQL,
+            params.push(older_than.to_rfc3339())
+        ));
+    }
+
+    let updated =
+        UserItemState::mark_read_matching(&state.pool, user.id, &conditions.join(" AND "), params)
+            .await?;
+    Ok(Json(MarkItemsResponse { updated }))
+}
+
+/// Star items
+///
+/// Stars all of the provided items for the current user. Items which were already starred
+/// keep the time they were first starred at. Ids of items which don't exist are ignored.
+#[utoipa::path(
+    post,
+    path = "/star",
+    tag = "items",
+    request_body = MarkItemsRequest,
+    responses(
+        (status = 200, description = "Items starred", body = MarkItemsResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn star(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Json(body): Json<MarkItemsRequest>,
+) -> Result<Json<MarkItemsResponse>> {
+    body.validate()?;
+
+    let updated = UserItemState::set_starred(&state.pool, user.id, &body.items, true).await?;
+    Ok(Json(MarkItemsResponse { updated }))
+}
+
+/// Unstar items
+///
+/// Unstars all of the provided items for the current user. Ids of items which don't exist are
+/// ignored.
+#[utoipa::path(
+    post,
+    path = "/unstar",
+    tag = "items",
+    request_body = MarkItemsRequest,
+    responses(
+        (status = 200, description = "Items unstarred", body = MarkItemsResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn unstar(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Json(body): Json<MarkItemsRequest>,
+) -> Result<Json<MarkItemsResponse>> {
+    body.validate()?;
+
+    let updated = UserItemState::set_starred(&state.pool, user.id, &body.items, false).await?;
+    Ok(Json(MarkItemsResponse { updated }))
+}
+
+/// List starred items
+///
+/// Returns the items the user has starred, most recently starred first, whether or not the
+/// user is still subscribed to their feed.
+#[utoipa::path(
+    get,
+    path = "/starred",
+    tag = "items",
+    params(CursorPagination),
+    responses(
+        (status = 200, description = "Starred items", body = Page<TimelineItem>),
+        (status = 400, description = "Invalid cursor"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn list_starred(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Query(pagination): Query<CursorPagination>,
+) -> Result<Json<Page<TimelineItem>>> {
+    let order = RowOrder::new("feed_item.id", true)
+        .key("user_item_state.starred_at", SortKeyType::Timestamp);
+    pagination.validate_for(&order)?;
+
+    let page = TimelineItem::fetch_page(
+        &state.pool,
+        user.id,
+        "user_item_state.starred",
+        &order,
+        &pagination,
+        SqlParams::new(),
+    )
+    .await?;
+    Ok(Json(page))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    fn post(uri: &str, items: &[i64]) -> http::Request<String> {
+        http::Request::builder()
+            .method(http::Method::POST)
+            .uri(uri)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::json!({ "items": items }).to_string())
+            .unwrap()
+    }
+
+    async fn list_starred(ctx: &TestContext, query: &str) -> Page<TimelineItem> {
+        let request = http::Request::builder()
+            .uri(format!("/item/starred?{}", query))
+            .body(String::new())
+            .unwrap();
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 200);
+        ctx.decode(response).await
+    }
+
+    #[tokio::test]
+    async fn stars_items() {
+        let ctx = TestContext::new().await;
+        insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        let first = insert_item(&ctx.pool, feed, "First", "").await;
+        let second = insert_item(&ctx.pool, feed, "Second", "").await;
+        let third = insert_item(&ctx.pool, feed, "Third", "").await;
+
+        let response = ctx.req(post("/item/star", &[second, first])).await;
+        let response: MarkItemsResponse = ctx.decode(response).await;
+        assert_eq!(response.updated, 2);
+        ctx.req(post("/item/star", &[third])).await;
+        // Starring again keeps the original time, so the item isn't moved to the top
+        ctx.req(post("/item/star", &[second])).await;
+
+        let page = list_starred(&ctx, "limit=2").await;
+        let ids: Vec<i64> = page.items.iter().map(|item| item.item.id).collect();
+        assert_eq!(ids[0], third);
+        assert!(page.items.iter().all(|item| item.starred));
+        assert!(page.items[0].starred_at.is_some());
+
+        let next_cursor = String::from(page.next_cursor.unwrap());
+        let page = list_starred(&ctx, &format!("limit=2&after={}", next_cursor)).await;
+        assert_eq!(page.items.len(), 1);
+        assert!(page.next_cursor.is_none());
+
+        ctx.req(post("/item/unstar", &[third, first])).await;
+        let page = list_starred(&ctx, "").await;
+        let ids: Vec<i64> = page.items.iter().map(|item| item.item.id).collect();
+        assert_eq!(ids, vec![second]);
+    }
+
+    async fn mark_all_read(ctx: &TestContext, body: serde_json::Value) -> u64 {
+        let request = http::Request::builder()
+            .method(http::Method::POST)
+            .uri("/item/mark-read")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .unwrap();
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 200);
+        ctx.decode::<MarkItemsResponse>(response).await.updated
+    }
+
+    #[tokio::test]
+    async fn marks_scopes_as_read() {
+        let ctx = TestContext::new().await;
+        insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        let tagged = insert_feed(&ctx.pool, "https://tagged.com/feed", "Tagged").await;
+        let other = insert_feed(&ctx.pool, "https://other.com/feed", "Other").await;
+        insert_subscription(&ctx.pool, 1, feed).await;
+        let tag = insert_tag(&ctx.pool, 1, "News", &[tagged]).await;
+        let old = insert_item(&ctx.pool, feed, "Old rust", "").await;
+        insert_item(&ctx.pool, feed, "New rust", "").await;
+        insert_item(&ctx.pool, feed, "Gardening", "").await;
+        insert_item(&ctx.pool, tagged, "Tagged rust", "").await;
+        insert_item(&ctx.pool, other, "Unsubscribed rust", "").await;
+        sqlx::query!(
+            "UPDATE feed_item SET published_at = NOW() - INTERVAL '1 day' WHERE id = $1",
+            old
+        )
+        .execute(&ctx.pool)
+        .await
+        .unwrap();
+
+        let older_than = chrono::Utc::now() - chrono::Duration::hours(1);
+        let body = serde_json::json!({ "scope": { "feed": feed }, "olderThan": older_than });
+        assert_eq!(mark_all_read(&ctx, body).await, 1);
+
+        let body = serde_json::json!({ "scope": { "query": "rust" } });
+        assert_eq!(mark_all_read(&ctx, body).await, 2);
+        let body = serde_json::json!({ "scope": { "tag": tag } });
+        assert_eq!(mark_all_read(&ctx, body).await, 0);
+
+        let body = serde_json::json!({ "scope": { "feed": feed } });
+        assert_eq!(mark_all_read(&ctx, body).await, 1);
+        let body = serde_json::json!({ "scope": { "feed": other } });
+        assert_eq!(mark_all_read(&ctx, body).await, 0);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::http::common::*;
+use crate::sql::Job;
+
+/// Get a job by ID
+///
+/// Returns the status and progress of a background job, such as an import, which clients can
+/// poll until its status is `completed` or `failed`. Failed attempts are retried with backoff,
+/// so a job with an `error` may still be `pending`, unless retrying can't help, as with an
+/// invalid file.
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "job",
+    params(
+        ("id" = i64, Path, description = "Job ID")
+    ),
+    responses(
+        (status = 200, description = "Job status and progress", body = Job),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the owner of the job"),
+        (status = 404, description = "Job not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn get_job(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Path(id): Path<i64>,
+) -> Result<Json<Job>> {
+    let job = Job::get(&state.pool, id).await?;
+    if job.owner != Some(user.id) {
+        return Err(Error::NotOwner);
+    }
+
+    Ok(Json(job))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::job::{run_next, JobKind};
+    use crate::sql::JobStatus;
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn fails_invalid_jobs_without_retrying() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+
+        let job = Job::enqueue(
+            &ctx.pool,
+            Some(user.id),
+            &JobKind::ImportOpml {
+                opml: "not opml".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        assert!(run_next(&ctx.pool).await.unwrap());
+
+        let request = http::Request::builder()
+            .uri(format!("/job/{}", job.id))
+            .body(String::new())
+            .unwrap();
+        let job: Job = ctx.decode(ctx.req(request).await).await;
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.attempts, 1);
+        assert!(job.error.is_some());
+        assert!(job.finished_at.is_some());
+        assert!(!run_next(&ctx.pool).await.unwrap());
+
+        sqlx::query!("UPDATE job SET owner = NULL")
+            .execute(&ctx.pool)
+            .await
+            .unwrap();
+        let request = http::Request::builder()
+            .uri(format!("/job/{}", job.id))
+            .body(String::new())
+            .unwrap();
+        assert_eq!(ctx.req(request).await.status(), 403);
+    }
+
+    #[tokio::test]
+    async fn retries_failed_jobs() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+
+        let job = Job::enqueue(
+            &ctx.pool,
+            Some(user.id),
+            &JobKind::ImportOpml {
+                opml: "<opml/>".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        let claimed = Job::claim(&ctx.pool).await.unwrap().unwrap();
+        claimed.fail(&ctx.pool, "Timed out", true).await.unwrap();
+        let job = Job::get(&ctx.pool, job.id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Pending);
+        assert_eq!(job.attempts, 1);
+        assert!(job.run_at > job.updated_at);
+        // The retry isn't due yet
+        assert!(Job::claim(&ctx.pool).await.unwrap().is_none());
+
+        // Runs out of attempts
+        sqlx::query!("UPDATE job SET run_at = NOW(), attempts = max_attempts - 1")
+            .execute(&ctx.pool)
+            .await
+            .unwrap();
+        let claimed = Job::claim(&ctx.pool).await.unwrap().unwrap();
+        claimed.fail(&ctx.pool, "Timed out", true).await.unwrap();
+        let job = Job::get(&ctx.pool, job.id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job.finished_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn fails_stale_jobs_out_of_attempts() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+
+        let job = Job::enqueue(
+            &ctx.pool,
+            Some(user.id),
+            &JobKind::ImportOpml {
+                opml: "not opml".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        // The worker died during the last attempt
+        sqlx::query!(
+            r#"
+            UPDATE job SET status = 'running', attempts = max_attempts,
+                updated_at = NOW() - INTERVAL '1 hour'
+            "#
+        )
+        .execute(&ctx.pool)
+        .await
+        .unwrap();
+        assert!(!run_next(&ctx.pool).await.unwrap());
+
+        let job = Job::get(&ctx.pool, job.id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job.error.is_some());
+        assert!(job.finished_at.is_some());
+    }
+}
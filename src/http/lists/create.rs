@@ -1,5 +1,5 @@
 use crate::http::common::*;
-use crate::sql::{Icon, InsertUserList, UserList};
+use crate::sql::{generate_slug, Icon, InsertUserList, UserList, Visibility, SLUG_REGEX};
 
 const MAX_LIST_COUNT: i64 = 500;
 
@@ -9,6 +9,12 @@ pub struct CreateListRequest {
     pub title: String,
     pub description: Option<String>,
     pub icon: Icon,
+    /// Who can see the list, private by default
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// Identifier of the list in its public URL, generated from the title by default
+    #[validate(length(min = 3, max = 64), regex(path = *SLUG_REGEX))]
+    pub slug: Option<String>,
 }
 
 /// Create a new list
@@ -22,6 +28,7 @@ pub struct CreateListRequest {
         (status = 400, description = "Invalid list parameters"),
         (status = 401, description = "Unauthorized"),
         (status = 403, description = "Reached max list count of 500"),
+        (status = 409, description = "Slug already in use"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -41,14 +48,19 @@ pub async fn create_list(
             "Reached max list count of 500".to_string(),
         ));
     }
+    let slug = body.slug.unwrap_or_else(|| generate_slug(&body.title));
+    let conflict = format!("Slug {} is already in use", slug);
 
     let query = InsertUserList {
         owner: user.id,
         title: body.title,
         description: body.description,
         icon: Some(body.icon),
+        visibility: body.visibility,
+        slug,
     }
     .insert(&state.pool)
-    .await?;
+    .await
+    .map_err(Error::conflict_on_unique(conflict))?;
     Ok(Json(query))
 }
@@ -1,5 +1,6 @@
 use crate::http::common::*;
-use crate::sql::UserListItem;
+use crate::query::SqlParams;
+use crate::sql::{fetch_page, CursorPagination, Page, RowOrder, SortKeyType, UserListItem};
 
 /// Get a specific item from a list
 #[utoipa::path(
@@ -40,16 +41,19 @@ pub async fn get_list_item(
 }
 
 /// Get all items from a list
+///
+/// Returns the most recently updated items of the list first, paginated with cursors.
 #[utoipa::path(
     get,
     path = "/{list_id}/item",
     tag = "lists",
     params(
         ("list_id" = i32, Path, description = "List ID"),
-        Pagination
+        CursorPagination
     ),
     responses(
-        (status = 200, description = "List of items in the list", body = Vec<UserListItem>),
+        (status = 200, description = "List of items in the list", body = Page<UserListItem>),
+        (status = 400, description = "Invalid pagination"),
         (status = 401, description = "Unauthorized"),
         (status = 404, description = "List not found"),
         (status = 500, description = "Internal server error")
@@ -61,23 +65,25 @@ pub async fn get_list_item(
 pub async fn get_list_items(
     _: AuthUser,
     State(state): State<ApiContext>,
-    Query(pagination): Query<Pagination>,
+    Query(pagination): Query<CursorPagination>,
     Path(list_id): Path<i32>,
-) -> Result<Json<Vec<UserListItem>>> {
-    let query = sqlx::query_as!(
-        UserListItem,
-        r#"
-      SELECT * FROM user_list_item
-      WHERE list = $1
-      ORDER BY updated_at DESC
-      LIMIT $2 OFFSET $3
-      "#,
-        list_id,
-        pagination.limit,
-        ((pagination.page - 1) * pagination.limit).into(),
+) -> Result<Json<Page<UserListItem>>> {
+    let order = RowOrder::new("user_list_item.id", true)
+        .key("user_list_item.updated_at", SortKeyType::Timestamp);
+    pagination.validate_for(&order)?;
+
+    let mut params = SqlParams::new();
+    let condition = format!("user_list_item.list = {}::integer", params.push(list_id));
+    let page = fetch_page(
+        &state.pool,
+        "user_list_item.*",
+        "user_list_item",
+        &condition,
+        &order,
+        &pagination,
+        params,
     )
-    .fetch_all(&state.pool)
     .await?;
 
-    Ok(Json(query))
+    Ok(Json(page))
 }
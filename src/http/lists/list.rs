@@ -1,14 +1,15 @@
 use crate::http::common::*;
 use crate::sql::Icon;
 use crate::sql::UserList;
+use crate::sql::Visibility;
 
-/// List all user lists
+/// List the user's lists
 #[utoipa::path(
     get,
     path = "/",
     tag = "lists",
     responses(
-        (status = 200, description = "List of all user lists", body = Vec<UserList>),
+        (status = 200, description = "List of the user's lists", body = Vec<UserList>),
         (status = 401, description = "Unauthorized"),
         (status = 500, description = "Internal server error")
     ),
@@ -17,17 +18,67 @@ use crate::sql::UserList;
     )
 )]
 pub async fn list_lists(
-    _user: AuthUser,
+    user: AuthUser,
     State(state): State<ApiContext>,
 ) -> Result<Json<Vec<UserList>>> {
     let values = sqlx::query_as!(
         UserList,
         r#"
-        SELECT id, owner, title, description, icon as "icon:Icon", created_at, updated_at FROM user_list
+        SELECT id, owner, title, description, icon as "icon:Icon", visibility as "visibility:Visibility", slug, created_at, updated_at FROM user_list
+        WHERE owner = $1
         ORDER BY updated_at DESC
         "#,
+        user.id
     )
     .fetch_all(&state.pool)
     .await?;
     Ok(Json(values))
 }
+
+#[cfg(test)]
+mod test {
+    use crate::sql::{generate_slug, InsertUser, InsertUserList};
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn lists_only_own_lists() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let other = InsertUser {
+            email: "other@example.com".to_string(),
+            username: "other".to_string(),
+            providers: vec![],
+            password_hash: None,
+        }
+        .insert(&ctx.pool)
+        .await
+        .unwrap();
+        let mut lists = vec![];
+        for owner in [user.id, other.id] {
+            let list = InsertUserList {
+                owner,
+                title: "Reading".to_string(),
+                description: None,
+                icon: None,
+                visibility: Visibility::Private,
+                slug: generate_slug("Reading"),
+            }
+            .insert(&ctx.pool)
+            .await
+            .unwrap();
+            lists.push(list.id);
+        }
+
+        let request = http::Request::builder()
+            .uri("/list")
+            .body(String::new())
+            .unwrap();
+        let response: Vec<UserList> = ctx.decode(ctx.req(request).await).await;
+        assert_eq!(
+            response.iter().map(|list| list.id).collect::<Vec<_>>(),
+            vec![lists[0]]
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::http::common::*;
-use crate::sql::{Icon, UserList};
+use crate::sql::{Icon, UserList, Visibility, SLUG_REGEX};
 
 #[derive(Deserialize, Validate, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -8,6 +8,9 @@ pub struct UpdateListRequest {
     title: Option<String>,
     description: Option<String>,
     icon: Option<Icon>,
+    visibility: Option<Visibility>,
+    #[validate(length(min = 3, max = 64), regex(path = *SLUG_REGEX))]
+    slug: Option<String>,
 }
 
 /// Update a list
@@ -25,6 +28,7 @@ pub struct UpdateListRequest {
         (status = 401, description = "Unauthorized"),
         (status = 403, description = "Not the owner of the list"),
         (status = 404, description = "List not found"),
+        (status = 409, description = "Slug already in use"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -52,9 +56,21 @@ pub async fn update_list(
     if let Some(icon) = body.icon {
         index.icon = Some(icon);
     }
+    if let Some(visibility) = body.visibility {
+        index.visibility = visibility;
+    }
+    if let Some(slug) = body.slug {
+        index.slug = slug;
+    }
 
     index.updated_at = chrono::Utc::now();
-    index.update(&state.pool).await?;
+    index
+        .update(&state.pool)
+        .await
+        .map_err(Error::conflict_on_unique(format!(
+            "Slug {} is already in use",
+            index.slug
+        )))?;
 
     Ok(Json(index))
 }
@@ -1,10 +1,15 @@
 pub mod common;
 pub mod error;
 pub mod feed;
+pub mod fever;
+pub mod greader;
 pub mod index;
 pub mod items;
+pub mod job;
 pub mod lists;
+pub mod public;
 pub mod search;
+pub mod subscription;
 pub mod tags;
 pub mod user;
 
@@ -61,10 +66,15 @@ use utoipa::{openapi::security::SecurityScheme, Modify};
         ),
         tags(
             (name = "feed", description = "Routes related to feed subscriptions and discovery"),
+            (name = "fever", description = "Fever compatible API for third-party clients"),
+            (name = "greader", description = "Google Reader compatible API for third-party clients"),
             (name = "index", description = "Routes related to feed indexes and aggregation"),
             (name = "items", description = "Routes related to feed items and content"),
+            (name = "job", description = "Routes related to background jobs"),
             (name = "lists", description = "Routes related to user-created lists"),
+            (name = "public", description = "Routes for indexes and lists shared without authentication"),
             (name = "search", description = "Routes related to search functionality"),
+            (name = "subscription", description = "Routes related to the feeds users are subscribed to"),
             (name = "user", description = "Routes related to user management"),
             (name = "user:email", description = "Routes related to email authentication"),
             (name = "user:oauth", description = "Routes related to OAuth authentication"),
@@ -111,10 +121,15 @@ impl Modify for SecurityAddon {
 pub fn router() -> OpenApiRouter<ApiContext> {
     OpenApiRouter::with_openapi(ApiDocs::openapi())
         .nest("/feed", feed::router())
+        .nest("/fever", fever::router())
+        .nest("/greader", greader::router())
         .nest("/index", index::router())
         .nest("/item", items::router())
+        .nest("/job", job::router())
         .nest("/list", lists::router())
+        .nest("/public", public::router())
         .nest("/search", search::router())
+        .nest("/subscription", subscription::router())
         .nest("/tag", tags::router())
         .nest("/user", user::router())
 }
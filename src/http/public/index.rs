@@ -0,0 +1,222 @@
+use crate::http::common::*;
+use crate::query::{parse_query, SqlParams};
+use crate::sql::{
+    fetch_page, subscribed_feeds_sql, CursorPagination, FeedItem, Icon, Page, SortOrder, UserIndex,
+    Visibility,
+};
+
+/// List public indexes
+///
+/// Returns the indexes their owners made public, most recently updated first. Unlisted
+/// indexes can only be opened from their link, so they aren't included.
+#[utoipa::path(
+    get,
+    path = "/index",
+    tag = "public",
+    params(
+        Pagination
+    ),
+    responses(
+        (status = 200, description = "List of public indexes", body = Vec<UserIndex>),
+        (status = 400, description = "Invalid pagination"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_public_indexes(
+    State(state): State<ApiContext>,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<Vec<UserIndex>>> {
+    pagination.validate()?;
+    let values = sqlx::query_as!(
+        UserIndex,
+        r#"
+        SELECT id, owner, query, sort as "sort:SortOrder", title, description, icon as "icon:Icon", visibility as "visibility:Visibility", slug, created_at, updated_at FROM user_index
+        WHERE visibility = 'public'
+        ORDER BY updated_at DESC
+        LIMIT $1 OFFSET $2
+        "#,
+        pagination.limit,
+        (pagination.page - 1) * pagination.limit,
+    ).fetch_all(&state.pool).await?;
+    Ok(Json(values))
+}
+
+/// Returns the index with the slug, unless it is private
+async fn get_shared_index(pool: &sqlx::PgPool, slug: String) -> Result<UserIndex> {
+    UserIndex::get_by_slug(pool, &slug)
+        .await?
+        .filter(|index| index.visibility != Visibility::Private)
+        .ok_or_else(|| Error::NotFound("Index not found".to_string()))
+}
+
+/// Get a shared index
+///
+/// Returns a public or unlisted index by its slug. Doesn't require authentication.
+#[utoipa::path(
+    get,
+    path = "/index/{slug}",
+    tag = "public",
+    params(
+        ("slug" = String, Path, description = "Slug of the index")
+    ),
+    responses(
+        (status = 200, description = "Index details", body = UserIndex),
+        (status = 404, description = "Index not found or private"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_public_index(
+    State(state): State<ApiContext>,
+    Path(slug): Path<String>,
+) -> Result<Json<UserIndex>> {
+    let index = get_shared_index(&state.pool, slug).await?;
+    Ok(Json(index))
+}
+
+/// List the items of a shared index
+///
+/// Runs the query of a public or unlisted index over the feeds its owner is subscribed to, like
+/// `GET /index/{id}/items`. Indexes filtering by the owner's read or starred state can't be
+/// shared, since their items would reveal it. Doesn't require authentication.
+#[utoipa::path(
+    get,
+    path = "/index/{slug}/items",
+    tag = "public",
+    params(
+        ("slug" = String, Path, description = "Slug of the index"),
+        CursorPagination
+    ),
+    responses(
+        (status = 200, description = "Items of the index", body = Page<FeedItem>),
+        (status = 400, description = "Invalid pagination"),
+        (status = 403, description = "Index filters by its owner's read or starred state"),
+        (status = 404, description = "Index not found or private"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_public_index_items(
+    State(state): State<ApiContext>,
+    Path(slug): Path<String>,
+    Query(pagination): Query<CursorPagination>,
+) -> Result<Json<Page<FeedItem>>> {
+    let index = get_shared_index(&state.pool, slug).await?;
+    let query = parse_query(&index.query)?;
+    if query.uses_item_state() {
+        return Err(Error::Forbidden(
+            "Index filters by its owner's read or starred state".to_string(),
+        ));
+    }
+
+    let mut params = SqlParams::new();
+    let owner = format!("{}::integer", params.push(index.owner));
+    let condition = format!(
+        "{} AND {}",
+        subscribed_feeds_sql(&owner),
+        query.to_sql_with_params(index.owner, &mut params)
+    );
+    let seed = format!("index-{}", index.id);
+    let order = index.sort.row_order(&query, &seed, &mut params);
+    pagination.validate_for(&order)?;
+
+    let page = fetch_page(
+        &state.pool,
+        "feed_item.*",
+        "feed_item",
+        &condition,
+        &order,
+        &pagination,
+        params,
+    )
+    .await?;
+    Ok(Json(page))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    /// Inserts an index with the query, shared with the visibility at the slug
+    async fn insert_shared_index(
+        pool: &sqlx::PgPool,
+        owner: i32,
+        query: &str,
+        visibility: Visibility,
+        slug: &str,
+    ) {
+        let mut index = insert_index(pool, owner, query, SortOrder::RecentlyUpdated).await;
+        index.visibility = visibility;
+        index.slug = slug.to_string();
+        index.update(pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn shares_indexes_by_visibility() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        insert_subscription(&ctx.pool, user.id, feed).await;
+        let item = insert_item(&ctx.pool, feed, "Rust news", "").await;
+        insert_item(&ctx.pool, feed, "Gardening", "").await;
+
+        for (title, visibility) in [
+            ("Private", Visibility::Private),
+            ("Unlisted", Visibility::Unlisted),
+            ("Public", Visibility::Public),
+        ] {
+            insert_shared_index(
+                &ctx.pool,
+                user.id,
+                "rust",
+                visibility,
+                &title.to_lowercase(),
+            )
+            .await;
+        }
+        let get = |path: &str| {
+            http::Request::builder()
+                .uri(path)
+                .body(String::new())
+                .unwrap()
+        };
+
+        let response = ctx.req(get("/public/index?page=1")).await;
+        let indexes: Vec<UserIndex> = ctx.decode(response).await;
+        assert_eq!(
+            indexes
+                .iter()
+                .map(|index| index.slug.as_str())
+                .collect::<Vec<_>>(),
+            vec!["public"]
+        );
+
+        let response = ctx.req(get("/public/index?page=0")).await;
+        assert_eq!(response.status(), 400);
+        let response = ctx.req(get("/public/index?page=1&limit=1000")).await;
+        assert_eq!(response.status(), 400);
+
+        let response = ctx.req(get("/public/index/private")).await;
+        assert_eq!(response.status(), 404);
+        let response = ctx.req(get("/public/index/unlisted")).await;
+        assert_eq!(response.status(), 200);
+
+        let response = ctx.req(get("/public/index/unlisted/items")).await;
+        let page: Page<FeedItem> = ctx.decode(response).await;
+        assert_eq!(
+            page.items.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![item]
+        );
+
+        insert_shared_index(
+            &ctx.pool,
+            user.id,
+            "is:unread",
+            Visibility::Unlisted,
+            "unread",
+        )
+        .await;
+        let response = ctx.req(get("/public/index/unread/items")).await;
+        assert_eq!(response.status(), 403);
+    }
+}
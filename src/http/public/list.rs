@@ -0,0 +1,119 @@
+use crate::http::common::*;
+use crate::query::SqlParams;
+use crate::sql::{
+    fetch_page, CursorPagination, FeedItem, Icon, Page, RowOrder, SortKeyType, UserList, Visibility,
+};
+
+/// List public lists
+///
+/// Returns the lists their owners made public, most recently updated first. Unlisted lists
+/// can only be opened from their link, so they aren't included.
+#[utoipa::path(
+    get,
+    path = "/list",
+    tag = "public",
+    params(
+        Pagination
+    ),
+    responses(
+        (status = 200, description = "List of public lists", body = Vec<UserList>),
+        (status = 400, description = "Invalid pagination"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_public_lists(
+    State(state): State<ApiContext>,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<Vec<UserList>>> {
+    pagination.validate()?;
+    let values = sqlx::query_as!(
+        UserList,
+        r#"
+        SELECT id, owner, title, description, icon as "icon:Icon", visibility as "visibility:Visibility", slug, created_at, updated_at FROM user_list
+        WHERE visibility = 'public'
+        ORDER BY updated_at DESC
+        LIMIT $1 OFFSET $2
+        "#,
+        pagination.limit,
+        (pagination.page - 1) * pagination.limit,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(values))
+}
+
+/// Returns the list with the slug, unless it is private
+async fn get_shared_list(pool: &sqlx::PgPool, slug: String) -> Result<UserList> {
+    UserList::get_by_slug(pool, &slug)
+        .await?
+        .filter(|list| list.visibility != Visibility::Private)
+        .ok_or_else(|| Error::NotFound("List not found".to_string()))
+}
+
+/// Get a shared list
+///
+/// Returns a public or unlisted list by its slug. Doesn't require authentication.
+#[utoipa::path(
+    get,
+    path = "/list/{slug}",
+    tag = "public",
+    params(
+        ("slug" = String, Path, description = "Slug of the list")
+    ),
+    responses(
+        (status = 200, description = "List details", body = UserList),
+        (status = 404, description = "List not found or private"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_public_list(
+    State(state): State<ApiContext>,
+    Path(slug): Path<String>,
+) -> Result<Json<UserList>> {
+    let list = get_shared_list(&state.pool, slug).await?;
+    Ok(Json(list))
+}
+
+/// List the items of a shared list
+///
+/// Returns the feed items of a public or unlisted list, most recently added first, paginated
+/// with cursors. Doesn't require authentication.
+#[utoipa::path(
+    get,
+    path = "/list/{slug}/items",
+    tag = "public",
+    params(
+        ("slug" = String, Path, description = "Slug of the list"),
+        CursorPagination
+    ),
+    responses(
+        (status = 200, description = "Items of the list", body = Page<FeedItem>),
+        (status = 400, description = "Invalid pagination"),
+        (status = 404, description = "List not found or private"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_public_list_items(
+    State(state): State<ApiContext>,
+    Path(slug): Path<String>,
+    Query(pagination): Query<CursorPagination>,
+) -> Result<Json<Page<FeedItem>>> {
+    let list = get_shared_list(&state.pool, slug).await?;
+    let order = RowOrder::new("user_list_item.id", true)
+        .key("user_list_item.updated_at", SortKeyType::Timestamp);
+    pagination.validate_for(&order)?;
+
+    let mut params = SqlParams::new();
+    let condition = format!("user_list_item.list = {}::integer", params.push(list.id));
+    let page = fetch_page(
+        &state.pool,
+        "feed_item.*",
+        "user_list_item JOIN feed_item ON feed_item.id = user_list_item.item",
+        &condition,
+        &order,
+        &pagination,
+        params,
+    )
+    .await?;
+    Ok(Json(page))
+}
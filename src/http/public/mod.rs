@@ -0,0 +1,17 @@
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use super::common::ApiContext;
+
+mod index;
+mod list;
+
+pub fn router() -> OpenApiRouter<ApiContext> {
+    OpenApiRouter::new()
+        .routes(routes!(index::list_public_indexes))
+        .routes(routes!(index::get_public_index))
+        .routes(routes!(index::get_public_index_items))
+        .routes(routes!(list::list_public_lists))
+        .routes(routes!(list::get_public_list))
+        .routes(routes!(list::get_public_list_items))
+}
@@ -0,0 +1,241 @@
+use crate::http::common::*;
+use crate::query::{parse_query, QueryError, SearchField, SqlParams};
+use crate::sql::{subscribed_feeds_sql, Feed, UserIndex};
+
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct ExplainRequest {
+    #[validate(length(min = 1, max = 1024))]
+    query: String,
+    /// Only count the items of the user's index with the ID which match the query
+    index: Option<i32>,
+}
+
+/// Tag of the user which a `tag:` value resolved to
+#[derive(Serialize, Deserialize, Debug, sqlx::FromRow, utoipa::ToSchema)]
+pub struct ExplainedTag {
+    pub id: i32,
+    pub title: String,
+    /// IDs of the feeds in the tag, which the query matches items from
+    pub feeds: Vec<i32>,
+}
+
+/// How a search query is understood
+#[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainResponse {
+    /// Expressions of the query, without redundant groups
+    #[schema(value_type = Vec<Object>)]
+    pub ast: serde_json::Value,
+    /// Feeds referenced with `feed:`
+    pub feeds: Vec<Feed>,
+    /// Tags referenced with `tag:`
+    pub tags: Vec<ExplainedTag>,
+    /// Values of `feed:` and `tag:` which don't match any feed or tag of the user, so
+    /// expressions using them never match
+    pub unresolved: Vec<String>,
+    /// Number of items from the user's subscriptions, or the index if one was given, matching
+    /// the query
+    pub count: i64,
+}
+
+/// Explain a search query
+///
+/// Parses the query without returning any items, to find out why a search or an index
+/// doesn't return what was expected. The response includes the parsed query, the feeds and
+/// tags its `feed:` and `tag:` fields refer to and how many items it matches, out of the
+/// items of the feeds the user is subscribed to or of one of their indexes.
+#[utoipa::path(
+    post,
+    path = "/explain",
+    tag = "search",
+    request_body = ExplainRequest,
+    responses(
+        (status = 200, description = "Explanation of the query", body = ExplainResponse),
+        (status = 400, description = "Invalid search query", body = QueryError),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the owner of the index"),
+        (status = 404, description = "Index not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn explain(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Json(body): Json<ExplainRequest>,
+) -> Result<Json<ExplainResponse>> {
+    body.validate()?;
+
+    let query = parse_query(&body.query)?.normalized();
+    let mut unresolved = vec![];
+
+    let feed_values = query.field_values(SearchField::Feed);
+    let feed_ids: Vec<i32> = feed_values
+        .iter()
+        .filter_map(|value| value.parse().ok())
+        .collect();
+    let feeds = sqlx::query_as::<_, Feed>("SELECT * FROM feed WHERE id = ANY($1) ORDER BY id")
+        .bind(&feed_ids)
+        .fetch_all(&state.pool)
+        .await?;
+    unresolved.extend(
+        feed_values
+            .into_iter()
+            .filter(|value| !feeds.iter().any(|feed| feed.id.to_string() == *value))
+            .map(|value| format!("feed:{}", value)),
+    );
+
+    let tag_values = query.field_values(SearchField::Tag);
+    let tags = sqlx::query_as!(
+        ExplainedTag,
+        r#"
+        SELECT id, title, ARRAY(
+            SELECT (child).id FROM unnest(children) AS child WHERE (child).type = 'feed'
+        ) AS "feeds!"
+        FROM user_tag
+        WHERE owner = $1 AND (id::text = ANY($2) OR lower(title) = ANY($3))
+        ORDER BY id
+        "#,
+        user.id,
+        &tag_values,
+        &tag_values
+            .iter()
+            .map(|value| value.to_lowercase())
+            .collect::<Vec<_>>()
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    unresolved.extend(
+        tag_values
+            .into_iter()
+            .filter(|value| {
+                !tags.iter().any(|tag| {
+                    tag.id.to_string() == *value || tag.title.to_lowercase() == value.to_lowercase()
+                })
+            })
+            .map(|value| format!("tag:{}", value)),
+    );
+
+    let mut params = SqlParams::new();
+    let owner = format!("{}::integer", params.push(user.id));
+    let mut conditions = vec![
+        subscribed_feeds_sql(&owner),
+        query.to_sql_with_params(user.id, &mut params),
+    ];
+    if let Some(id) = body.index {
+        let index = UserIndex::get(&state.pool, id).await?;
+        if index.owner != user.id {
+            return Err(Error::NotOwner);
+        }
+        conditions.push(parse_query(&index.query)?.to_sql_with_params(user.id, &mut params));
+    }
+    let sql = format!(
+        "SELECT COUNT(*) FROM feed_item WHERE {}",
+        conditions.join(" AND ")
+    );
+    let mut count_query = sqlx::query_scalar::<_, i64>(&sql);
+    for param in params.into_inner() {
+        count_query = count_query.bind(param);
+    }
+    let count = count_query.fetch_one(&state.pool).await?;
+
+    Ok(Json(ExplainResponse {
+        ast: serde_json::to_value(&query.exprs).map_err(anyhow::Error::from)?,
+        feeds,
+        tags,
+        unresolved,
+        count,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sql::{FeedItem, Page, SortOrder};
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    fn explain_request(body: serde_json::Value) -> http::Request<String> {
+        http::Request::builder()
+            .method(http::Method::POST)
+            .uri("/search/explain")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn explains_queries() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        let tag = insert_tag(&ctx.pool, user.id, "News", &[feed]).await;
+        insert_item(&ctx.pool, feed, "Rust news", "").await;
+        insert_item(&ctx.pool, feed, "Gardening", "").await;
+
+        let request = explain_request(serde_json::json!({
+            "query": format!("(rust) feed:{} tag:news tag:missing", feed)
+        }));
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 200);
+
+        let explained: ExplainResponse = ctx.decode(response).await;
+        assert_eq!(
+            explained.ast[0],
+            serde_json::json!({ "type": "word", "value": "rust" })
+        );
+        assert_eq!(
+            explained
+                .feeds
+                .iter()
+                .map(|feed| feed.id)
+                .collect::<Vec<_>>(),
+            vec![feed]
+        );
+        assert_eq!(explained.tags.len(), 1);
+        assert_eq!(explained.tags[0].id, tag);
+        assert_eq!(explained.tags[0].feeds, vec![feed]);
+        assert_eq!(explained.unresolved, vec!["tag:missing"]);
+        // The missing tag doesn't match any feed, so no items do either
+        assert_eq!(explained.count, 0);
+
+        let response = ctx
+            .req(explain_request(serde_json::json!({ "query": "" })))
+            .await;
+        assert_eq!(response.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn counts_items_of_subscriptions_and_indexes() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        let other = insert_feed(&ctx.pool, "https://other.com/feed", "Other").await;
+        insert_subscription(&ctx.pool, user.id, feed).await;
+        insert_item(&ctx.pool, feed, "Rust news", "").await;
+        insert_item(&ctx.pool, feed, "Rust gardening", "").await;
+        insert_item(&ctx.pool, other, "Rust elsewhere", "").await;
+        let index = insert_index(&ctx.pool, user.id, "news", SortOrder::AsIs).await;
+
+        let response = ctx
+            .req(explain_request(serde_json::json!({ "query": "rust" })))
+            .await;
+        let explained: ExplainResponse = ctx.decode(response).await;
+        assert_eq!(explained.count, 2);
+        // Searching finds the same items
+        let request = http::Request::builder()
+            .method(http::Method::POST)
+            .uri("/search")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::json!({ "query": "rust", "sort": "AsIs" }).to_string())
+            .unwrap();
+        let page: Page<FeedItem> = ctx.decode(ctx.req(request).await).await;
+        assert_eq!(page.items.len() as i64, explained.count);
+
+        let body = serde_json::json!({ "query": "rust", "index": index.id });
+        let explained: ExplainResponse = ctx.decode(ctx.req(explain_request(body)).await).await;
+        assert_eq!(explained.count, 1);
+    }
+}
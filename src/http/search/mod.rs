@@ -3,28 +3,84 @@ use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 
 use crate::http::common::*;
-use crate::query::Query as SearchQuery;
-use crate::sql::{FeedItem, SortOrder};
+use crate::query::{Query as SearchQuery, QueryError, SqlParams};
+use crate::sql::{fetch_page, subscribed_feeds_sql, CursorPagination, FeedItem, Page, SortOrder};
+
+mod explain;
 
 #[derive(Deserialize, Validate, utoipa::ToSchema)]
 pub struct SearchRequest {
-    #[validate(custom(function = "crate::query::validate_query"))]
     query: String,
     sort: SortOrder,
+    /// Whether to include highlighted snippets and the rank of each hit
+    #[serde(default)]
+    highlight: bool,
+}
+
+/// A feed item matching a search query. The snippets and rank are only included when
+/// highlighting is requested and the query searches for words or phrases
+#[derive(Clone, Debug, Serialize, Deserialize, sqlx::FromRow, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub item: FeedItem,
+    /// Title of the item, with the matches wrapped in `<mark>` tags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title_highlight: Option<String>,
+    /// Fragments of the content or description of the item around the matches, separated
+    /// with ` … ` and with the matches wrapped in `<mark>` tags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_highlight: Option<String>,
+    /// How relevant the item is to the words and phrases of the query
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<f32>,
+}
+
+const TITLE_HEADLINE_OPTIONS: &str = "HighlightAll=true, StartSel=<mark>, StopSel=</mark>";
+const CONTENT_HEADLINE_OPTIONS: &str =
+    "StartSel=<mark>, StopSel=</mark>, MaxFragments=3, MaxWords=20, MinWords=5, FragmentDelimiter=\" … \"";
+
+/// Returns the columns of a search hit, with highlighted snippets and the rank of the item
+/// when `tsquery` is given
+fn search_hit_columns(tsquery: Option<String>, params: &mut SqlParams) -> String {
+    match tsquery {
+        Some(tsquery) => format!(
+            "feed_item.*, \
+            ts_headline('english', feed_item.title, {0}, {1}) AS title_highlight, \
+            ts_headline('english', COALESCE(feed_item.content, feed_item.description, ''), {0}, {2}) \
+            AS content_highlight, \
+            ts_rank(feed_item.search_vector, {0}) AS rank",
+            tsquery,
+            params.push(TITLE_HEADLINE_OPTIONS),
+            params.push(CONTENT_HEADLINE_OPTIONS)
+        ),
+        None => "feed_item.*, NULL::text AS title_highlight, NULL::text AS content_highlight, \
+            NULL::real AS rank"
+            .to_string(),
+    }
 }
 
 /// Search for feed items
+///
+/// Searches the items of the feeds the user is subscribed to, directly or through tags.
+/// Words and phrases are matched against the title, description and content of items using
+/// Postgres full-text search. With the `Relevance` sort, items are ranked by how relevant they
+/// are to them. Results are paginated with cursors, see [CursorPagination].
+///
+/// With `highlight`, each hit also includes snippets of its title and content with the matches
+/// marked and its rank, to show why it matched.
 #[utoipa::path(
     post,
     path = "/",
     tag = "search",
     request_body = SearchRequest,
     params(
-        Pagination
+        CursorPagination
     ),
     responses(
-        (status = 200, description = "List of feed items matching the search query", body = Vec<FeedItem>),
-        (status = 400, description = "Invalid search query"),
+        (status = 200, description = "Page of feed items matching the search query", body = Page<SearchHit>),
+        (status = 400, description = "Invalid search query", body = QueryError),
         (status = 401, description = "Unauthorized"),
         (status = 500, description = "Internal server error")
     ),
@@ -33,35 +89,305 @@ pub struct SearchRequest {
     )
 )]
 pub async fn search(
-    _user: AuthUser,
+    user: AuthUser,
     State(state): State<ApiContext>,
-    Query(pagination): Query<Pagination>,
+    Query(pagination): Query<CursorPagination>,
     Json(body): Json<SearchRequest>,
-) -> Result<Json<Vec<FeedItem>>> {
+) -> Result<Json<Page<SearchHit>>> {
     body.validate()?;
-    let query = SearchQuery::try_from(body.query)
-        .expect("This should not be possible because the query is validated");
-    // TODO: Think about this behavior of querying
-    let (sql, mut params) = query.to_sql();
-    let order_by_column = match body.sort {
-        SortOrder::AsIs => "index_in_feed",
-        SortOrder::RecentlyUpdated => "updated_at",
+    let query = SearchQuery::try_from(body.query)?;
+
+    let mut params = SqlParams::new();
+    let owner = format!("{}::integer", params.push(user.id));
+    let condition = format!(
+        "{} AND {}",
+        subscribed_feeds_sql(&owner),
+        query.to_sql_with_params(user.id, &mut params)
+    );
+    // Shuffle results differently for each user, but the same way on every page
+    let seed = format!("user-{}", user.id);
+    let order = body.sort.row_order(&query, &seed, &mut params);
+    pagination.validate_for(&order)?;
+    let tsquery = match body.highlight {
+        true => query.tsquery_sql(&mut params),
+        false => None,
     };
-    let paginated_sql = format!("{} ORDER BY {} LIMIT ? OFFSET ?", sql, order_by_column);
+    let columns = search_hit_columns(tsquery, &mut params);
+
+    let page = fetch_page(
+        &state.pool,
+        &columns,
+        "feed_item",
+        &condition,
+        &order,
+        &pagination,
+        params,
+    )
+    .await?;
+    Ok(Json(page))
+}
+
+pub fn router() -> OpenApiRouter<ApiContext> {
+    OpenApiRouter::new()
+        .routes(routes!(search))
+        .routes(routes!(explain::explain))
+}
 
-    params.push(pagination.limit.to_string());
-    params.push(((pagination.page - 1) * pagination.limit).to_string());
+#[cfg(test)]
+mod test {
+    use crate::tests::{fixtures::*, TestContext};
 
-    let mut query = sqlx::query_as::<_, FeedItem>(&paginated_sql);
+    use super::*;
 
-    for param in params {
-        query = query.bind(param);
+    fn search_request(query: &str) -> http::Request<String> {
+        sorted_search_request(query, "Relevance")
     }
 
-    let values = query.fetch_all(&state.pool).await?;
-    Ok(Json(values))
-}
+    fn sorted_search_request(query: &str, sort: &str) -> http::Request<String> {
+        http::Request::builder()
+            .method(http::Method::POST)
+            .uri("/search")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::json!({ "query": query, "sort": sort }).to_string())
+            .unwrap()
+    }
 
-pub fn router() -> OpenApiRouter<ApiContext> {
-    OpenApiRouter::new().routes(routes!(search))
+    #[tokio::test]
+    async fn ranks_full_text_matches() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        insert_subscription(&ctx.pool, user.id, feed).await;
+
+        // The less relevant item is the most recent one, so it would come first without ranking
+        let in_title = insert_item(&ctx.pool, feed, "Compilers in Rust", "").await;
+        let in_description = insert_item(
+            &ctx.pool,
+            feed,
+            "Weekly update",
+            "Notes on compilers written in Rust",
+        )
+        .await;
+        insert_item(&ctx.pool, feed, "Gardening", "Growing tomatoes").await;
+        // Items of feeds the user isn't subscribed to aren't searched
+        let unsubscribed = insert_feed(&ctx.pool, "https://other.com/feed", "Other").await;
+        insert_item(&ctx.pool, unsubscribed, "Rust compilers", "").await;
+
+        let response = ctx.req(search_request("compiler rust")).await;
+        assert_eq!(response.status(), 200);
+
+        let items = ctx.decode::<Page<FeedItem>>(response).await.items;
+        let ids = items.iter().map(|item| item.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![in_title, in_description]);
+
+        let response = ctx.req(search_request("\"rust compilers\"")).await;
+        let items = ctx.decode::<Page<FeedItem>>(response).await.items;
+        assert!(items.is_empty());
+    }
+
+    #[tokio::test]
+    async fn highlights_matches() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        insert_subscription(&ctx.pool, user.id, feed).await;
+        insert_item(
+            &ctx.pool,
+            feed,
+            "Compilers in Rust",
+            "Writing a compiler is easier than it looks",
+        )
+        .await;
+
+        let request = http::Request::builder()
+            .method(http::Method::POST)
+            .uri("/search")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(
+                serde_json::json!({ "query": "compiler", "sort": "Relevance", "highlight": true })
+                    .to_string(),
+            )
+            .unwrap();
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 200);
+
+        let hits = ctx.decode::<Page<SearchHit>>(response).await.items;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].title_highlight.as_deref(),
+            Some("<mark>Compilers</mark> in Rust")
+        );
+        assert!(hits[0]
+            .content_highlight
+            .as_deref()
+            .unwrap()
+            .contains("<mark>compiler</mark>"));
+        assert!(hits[0].rank.unwrap() > 0.0);
+
+        // Without highlighting, hits are plain items
+        let response = ctx.req(search_request("compiler")).await;
+        let hits = ctx.decode::<Page<SearchHit>>(response).await.items;
+        assert!(hits[0].title_highlight.is_none());
+        assert!(hits[0].rank.is_none());
+    }
+
+    #[tokio::test]
+    async fn filters_by_read_state() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        insert_subscription(&ctx.pool, user.id, feed).await;
+        let read = insert_item(&ctx.pool, feed, "Read", "").await;
+        let unread = insert_item(&ctx.pool, feed, "Unread", "").await;
+
+        let request = http::Request::builder()
+            .method(http::Method::POST)
+            .uri("/item/read")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::json!({ "items": [read, read] }).to_string())
+            .unwrap();
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 200);
+
+        let response = ctx.req(search_request("is:unread")).await;
+        let items = ctx.decode::<Page<FeedItem>>(response).await.items;
+        assert_eq!(
+            items.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![unread]
+        );
+
+        let response = ctx.req(search_request("is:read")).await;
+        let items = ctx.decode::<Page<FeedItem>>(response).await.items;
+        assert_eq!(
+            items.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![read]
+        );
+    }
+
+    #[tokio::test]
+    async fn filters_by_fields() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let blog = insert_feed(&ctx.pool, "https://blog.example.com/feed", "Blog").await;
+        let other = insert_feed(&ctx.pool, "https://other.com/feed", "Other").await;
+        insert_subscription(&ctx.pool, user.id, blog).await;
+        insert_subscription(&ctx.pool, user.id, other).await;
+        sqlx::query!(
+            "UPDATE feed SET domain = $1 WHERE id = $2",
+            "blog.example.com",
+            blog
+        )
+        .execute(&ctx.pool)
+        .await
+        .unwrap();
+        let blog_item = insert_item(&ctx.pool, blog, "Blog post", "").await;
+        let other_item = insert_item(&ctx.pool, other, "Other post", "").await;
+
+        let response = ctx.req(search_request("domain:example.com")).await;
+        let items = ctx.decode::<Page<FeedItem>>(response).await.items;
+        assert_eq!(
+            items.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![blog_item]
+        );
+
+        let response = ctx
+            .req(search_request(&format!("feed:{} title:post", other)))
+            .await;
+        let items = ctx.decode::<Page<FeedItem>>(response).await.items;
+        assert_eq!(
+            items.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![other_item]
+        );
+
+        let response = ctx.req(search_request("type:video")).await;
+        assert_eq!(response.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn reports_query_errors() {
+        let ctx = TestContext::new().await;
+
+        let response = ctx.req(search_request("rust titel:compilers")).await;
+        assert_eq!(response.status(), 400);
+
+        let error: serde_json::Value = ctx.decode(response).await;
+        assert_eq!(error["offset"], 5);
+        assert_eq!(error["suggestions"], serde_json::json!(["title"]));
+        assert_eq!(
+            error["message"],
+            "Unknown field `titel`, did you mean `title`?"
+        );
+    }
+
+    #[tokio::test]
+    async fn paginates_with_cursors() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        insert_subscription(&ctx.pool, user.id, feed).await;
+        let first = insert_item(&ctx.pool, feed, "First post", "").await;
+        let second = insert_item(&ctx.pool, feed, "Second post", "").await;
+        let third = insert_item(&ctx.pool, feed, "Third post", "").await;
+
+        let search = |pagination: String| {
+            http::Request::builder()
+                .method(http::Method::POST)
+                .uri(format!("/search?limit=2{}", pagination))
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(serde_json::json!({ "query": "post", "sort": "RecentlyUpdated" }).to_string())
+                .unwrap()
+        };
+        let ids = |page: &Page<FeedItem>| page.items.iter().map(|item| item.id).collect::<Vec<_>>();
+
+        let page: Page<FeedItem> = ctx.decode(ctx.req(search(String::new())).await).await;
+        assert_eq!(ids(&page), vec![third, second]);
+        assert!(page.prev_cursor.is_none());
+        let next_cursor = String::from(page.next_cursor.unwrap());
+
+        let page: Page<FeedItem> = ctx
+            .decode(ctx.req(search(format!("&after={}", next_cursor))).await)
+            .await;
+        assert_eq!(ids(&page), vec![first]);
+        assert!(page.next_cursor.is_none());
+        let prev_cursor = String::from(page.prev_cursor.unwrap());
+
+        let page: Page<FeedItem> = ctx
+            .decode(ctx.req(search(format!("&before={}", prev_cursor))).await)
+            .await;
+        assert_eq!(ids(&page), vec![third, second]);
+
+        let response = ctx.req(search("&after=nonsense".to_string())).await;
+        assert_eq!(response.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn sorts_by_published_date() {
+        let ctx = TestContext::new().await;
+        let user = insert_user(&ctx.pool).await;
+        let feed = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+        insert_subscription(&ctx.pool, user.id, feed).await;
+        let old = insert_item(&ctx.pool, feed, "Old post", "").await;
+        let new = insert_item(&ctx.pool, feed, "New post", "").await;
+        // Items without a publication date are sorted by when they were added
+        sqlx::query!(
+            "UPDATE feed_item SET published_at = NOW() - INTERVAL '1 day' WHERE id = $1",
+            old
+        )
+        .execute(&ctx.pool)
+        .await
+        .unwrap();
+
+        for (sort, expected) in [("Published", vec![new, old]), ("Oldest", vec![old, new])] {
+            let response = ctx.req(sorted_search_request("post", sort)).await;
+            let items = ctx.decode::<Page<FeedItem>>(response).await.items;
+            assert_eq!(
+                items.iter().map(|item| item.id).collect::<Vec<_>>(),
+                expected
+            );
+        }
+
+        let response = ctx.req(sorted_search_request("post", "Random")).await;
+        let items = ctx.decode::<Page<FeedItem>>(response).await.items;
+        assert_eq!(items.len(), 2);
+    }
 }
@@ -0,0 +1,127 @@
+use crate::http::common::*;
+use crate::sql::{Icon, UserFeed};
+
+#[derive(Deserialize, Serialize, Validate, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscribeRequest {
+    /// URL of the feed to subscribe to
+    #[validate(url)]
+    link: String,
+    /// Title to show for the feed, the feed's own title by default
+    #[validate(length(min = 1, max = 1024))]
+    title: Option<String>,
+    /// Icon to show for the feed, the icon of its website by default
+    icon: Option<Icon>,
+}
+
+/// Subscribe to a feed
+///
+/// Subscribes the user to the feed at the URL, adding the feed to the system first if no one
+/// has subscribed to it yet. If the user is already subscribed to the feed, the existing
+/// subscription is returned unchanged.
+#[utoipa::path(
+    put,
+    path = "/",
+    tag = "subscription",
+    request_body = SubscribeRequest,
+    responses(
+        (status = 200, description = "Already subscribed to the feed", body = UserFeed),
+        (status = 201, description = "Subscribed to the feed", body = UserFeed),
+        (status = 400, description = "Invalid URL or the feed could not be fetched"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn subscribe(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Json(body): Json<SubscribeRequest>,
+) -> Result<impl IntoResponse> {
+    body.validate()?;
+
+    let (subscription, created) =
+        UserFeed::subscribe(&state.pool, user.id, &body.link, body.title, body.icon).await?;
+    let status = match created {
+        true => http::StatusCode::CREATED,
+        false => http::StatusCode::OK,
+    };
+    Ok((status, Json(subscription)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn manages_subscriptions() {
+        let mut server = mockito::Server::new_async().await;
+        let _feed = server
+            .mock("GET", "/rss")
+            .with_header("Content-Type", "application/rss+xml")
+            .with_body_from_file("tests/feeds/hacker-news-rss.xml")
+            .create_async()
+            .await;
+
+        let ctx = TestContext::new().await;
+        insert_user(&ctx.pool).await;
+        insert_feed(&ctx.pool, "https://other.com/feed", "Other").await;
+
+        let subscribe = || {
+            http::Request::builder()
+                .method(http::Method::PUT)
+                .uri("/subscription")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(serde_json::json!({ "link": format!("{}/rss", server.url()) }).to_string())
+                .unwrap()
+        };
+        let response = ctx.req(subscribe()).await;
+        assert_eq!(response.status(), 201);
+        let subscription: UserFeed = ctx.decode(response).await;
+        assert_eq!(subscription.title, "Hacker News");
+
+        // Subscribing again returns the same subscription
+        let response = ctx.req(subscribe()).await;
+        assert_eq!(response.status(), 200);
+        let again: UserFeed = ctx.decode(response).await;
+        assert_eq!(again.id, subscription.id);
+
+        let request = http::Request::builder()
+            .method(http::Method::PATCH)
+            .uri(format!("/subscription/{}", subscription.id))
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::json!({ "title": "HN" }).to_string())
+            .unwrap();
+        let renamed: UserFeed = ctx.decode(ctx.req(request).await).await;
+        assert_eq!(renamed.title, "HN");
+
+        // Only the feeds the user is subscribed to are listed
+        let request = http::Request::builder()
+            .uri("/feed")
+            .body(String::new())
+            .unwrap();
+        let feeds: Vec<Feed> = ctx.decode(ctx.req(request).await).await;
+        assert_eq!(
+            feeds.iter().map(|feed| feed.id).collect::<Vec<_>>(),
+            vec![subscription.feed]
+        );
+
+        let request = http::Request::builder()
+            .method(http::Method::DELETE)
+            .uri(format!("/subscription/{}", subscription.id))
+            .body(String::new())
+            .unwrap();
+        assert_eq!(ctx.req(request).await.status(), 204);
+
+        let request = http::Request::builder()
+            .uri("/subscription")
+            .body(String::new())
+            .unwrap();
+        let subscriptions: Vec<UserFeed> = ctx.decode(ctx.req(request).await).await;
+        assert!(subscriptions.is_empty());
+    }
+}
@@ -0,0 +1,38 @@
+use crate::http::common::*;
+use crate::sql::UserFeed;
+
+/// Unsubscribe from a feed
+///
+/// Deletes the subscription. The feed itself is kept, since other users may be subscribed to
+/// it, and stays in any of the user's tags it was added to.
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "subscription",
+    params(
+        ("id" = i32, Path, description = "Subscription ID")
+    ),
+    responses(
+        (status = 204, description = "Unsubscribed successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the owner of the subscription"),
+        (status = 404, description = "Subscription not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn unsubscribe(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse> {
+    let subscription = UserFeed::get(&state.pool, id).await?;
+    if subscription.owner != user.id {
+        return Err(Error::NotOwner);
+    }
+
+    subscription.delete(&state.pool).await?;
+    Ok(http::StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,182 @@
+use axum::http::{header, HeaderValue};
+
+use crate::feed::opml::{Opml, Outline};
+use crate::http::common::*;
+
+/// Export subscriptions
+///
+/// Returns an OPML file of the feeds the user is subscribed to, which other feed readers can
+/// import. Each tag is a folder with the feeds it contains, and subscriptions which aren't in
+/// any tag are listed after the folders. Feeds use the titles the user gave them.
+#[utoipa::path(
+    get,
+    path = "/export.opml",
+    tag = "subscription",
+    responses(
+        (status = 200, description = "OPML file of the subscriptions", content_type = "text/x-opml"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn export_subscriptions(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+) -> Result<impl IntoResponse> {
+    let tagged = sqlx::query!(
+        r#"
+        SELECT user_tag.id, user_tag.title AS tag, COALESCE(user_feed.title, feed.title) AS "title!", feed.link
+        FROM user_tag
+        CROSS JOIN LATERAL unnest(user_tag.children) AS child
+        JOIN feed ON feed.id = (child).id
+        LEFT JOIN user_feed ON user_feed.feed = feed.id AND user_feed.owner = user_tag.owner
+        WHERE user_tag.owner = $1 AND (child).type = 'feed'
+        ORDER BY lower(user_tag.title), user_tag.id, lower(COALESCE(user_feed.title, feed.title))
+        "#,
+        user.id
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    let untagged = sqlx::query!(
+        r#"
+        SELECT user_feed.title, feed.link FROM user_feed
+        JOIN feed ON feed.id = user_feed.feed
+        WHERE user_feed.owner = $1 AND NOT EXISTS (
+            SELECT 1 FROM user_tag, unnest(user_tag.children) AS child
+            WHERE user_tag.owner = $1 AND (child).type = 'feed' AND (child).id = user_feed.feed
+        )
+        ORDER BY lower(user_feed.title)
+        "#,
+        user.id
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let mut outlines: Vec<Outline> = vec![];
+    let mut folder = None;
+    for row in tagged {
+        if folder != Some(row.id) {
+            folder = Some(row.id);
+            outlines.push(Outline::folder(row.tag, vec![]));
+        }
+        if let Some(outline) = outlines.last_mut() {
+            outline.children.push(Outline::feed(row.title, row.link));
+        }
+    }
+    outlines.extend(
+        untagged
+            .into_iter()
+            .map(|row| Outline::feed(row.title, row.link)),
+    );
+
+    let opml = Opml {
+        title: Some("Bind subscriptions".to_string()),
+        outlines,
+    };
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/x-opml; charset=utf-8"),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                HeaderValue::from_static("attachment; filename=\"subscriptions.opml\""),
+            ),
+        ],
+        opml.to_xml(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use http_body_util::BodyExt;
+
+    use crate::job::run_next;
+    use crate::sql::{Job, JobStatus};
+    use crate::tests::{fixtures::*, TestContext};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn imports_and_exports_opml() {
+        let mut server = mockito::Server::new_async().await;
+        let _feed = server
+            .mock("GET", "/rss")
+            .with_header("Content-Type", "application/rss+xml")
+            .with_body_from_file("tests/feeds/hacker-news-rss.xml")
+            .create_async()
+            .await;
+        let _missing = server
+            .mock("GET", "/missing")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let ctx = TestContext::new().await;
+        insert_user(&ctx.pool).await;
+        let existing = insert_feed(&ctx.pool, "https://example.com/feed", "Example").await;
+
+        let opml = format!(
+            r#"<opml version="2.0"><body>
+                <outline text="News">
+                    <outline text="HN" xmlUrl="{0}/rss"/>
+                    <outline text="Nested"><outline text="Example" xmlUrl="https://example.com/feed"/></outline>
+                </outline>
+                <outline text="Missing" xmlUrl="{0}/missing"/>
+            </body></opml>"#,
+            server.url()
+        );
+        let request = http::Request::builder()
+            .method(http::Method::POST)
+            .uri("/subscription/import")
+            .header(header::CONTENT_TYPE, "text/x-opml")
+            .body(opml)
+            .unwrap();
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 202);
+        let job: Job = ctx.decode(response).await;
+        assert_eq!(job.kind, "import_opml");
+        assert_eq!(job.status, JobStatus::Pending);
+
+        while run_next(&ctx.pool).await.unwrap() {}
+        let request = http::Request::builder()
+            .uri(format!("/job/{}", job.id))
+            .body(String::new())
+            .unwrap();
+        let job: Job = ctx.decode(ctx.req(request).await).await;
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!((job.progress, job.total), (3, Some(3)));
+        assert_eq!(
+            job.result,
+            Some(serde_json::json!({ "failed": [format!("{}/missing", server.url())] }))
+        );
+
+        let request = http::Request::builder()
+            .uri("/subscription/export.opml")
+            .body(String::new())
+            .unwrap();
+        let response = ctx.req(request).await;
+        assert_eq!(response.status(), 200);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let exported = Opml::parse(std::str::from_utf8(&body).unwrap()).unwrap();
+        assert_eq!(
+            exported.outlines,
+            vec![Outline::folder(
+                "News",
+                vec![
+                    Outline::feed("Example", "https://example.com/feed"),
+                    Outline::feed("HN", format!("{}/rss", server.url())),
+                ]
+            )]
+        );
+        let subscribed =
+            sqlx::query_scalar!("SELECT COUNT(*) FROM user_feed WHERE feed = $1", existing)
+                .fetch_one(&ctx.pool)
+                .await
+                .unwrap();
+        assert_eq!(subscribed, Some(1));
+    }
+}
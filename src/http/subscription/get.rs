@@ -0,0 +1,34 @@
+use crate::http::common::*;
+use crate::sql::UserFeed;
+
+/// Get a subscription by ID
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "subscription",
+    params(
+        ("id" = i32, Path, description = "Subscription ID")
+    ),
+    responses(
+        (status = 200, description = "Subscription details", body = UserFeed),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the owner of the subscription"),
+        (status = 404, description = "Subscription not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn get_subscription(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Path(id): Path<i32>,
+) -> Result<Json<UserFeed>> {
+    let subscription = UserFeed::get(&state.pool, id).await?;
+    if subscription.owner != user.id {
+        return Err(Error::NotOwner);
+    }
+
+    Ok(Json(subscription))
+}
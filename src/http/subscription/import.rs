@@ -0,0 +1,46 @@
+use crate::feed::opml::Opml;
+use crate::http::common::*;
+use crate::job::JobKind;
+use crate::sql::Job;
+
+/// Import subscriptions
+///
+/// Subscribes the user to every feed of an OPML file, as exported by other feed readers, adding
+/// the feeds which aren't in the system yet. Top-level folders become tags containing the feeds
+/// inside them, including the ones in nested folders, and are merged into existing tags with
+/// the same title.
+///
+/// Adding feeds requires fetching them, so the import runs as a background job whose progress
+/// can be followed with `GET /job/{id}`. Once completed, the result of the job lists the links
+/// of the feeds which couldn't be subscribed to in `failed`.
+#[utoipa::path(
+    post,
+    path = "/import",
+    tag = "subscription",
+    request_body(content = String, content_type = "text/x-opml", description = "OPML file"),
+    responses(
+        (status = 202, description = "Import queued", body = Job),
+        (status = 400, description = "Invalid OPML file"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn import_subscriptions(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    body: String,
+) -> Result<impl IntoResponse> {
+    // Rejects invalid files right away, rather than in the job
+    Opml::parse(&body)?;
+
+    let job = Job::enqueue(
+        &state.pool,
+        Some(user.id),
+        &JobKind::ImportOpml { opml: body },
+    )
+    .await?;
+    Ok((http::StatusCode::ACCEPTED, Json(job)))
+}
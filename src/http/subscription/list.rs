@@ -0,0 +1,37 @@
+use crate::http::common::*;
+use crate::sql::{Icon, UserFeed};
+
+/// List subscriptions
+///
+/// Returns the feeds the user is subscribed to, with the title and icon they gave them, in
+/// alphabetical order.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "subscription",
+    responses(
+        (status = 200, description = "Subscriptions of the user", body = Vec<UserFeed>),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn list_subscriptions(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+) -> Result<Json<Vec<UserFeed>>> {
+    let subscriptions = sqlx::query_as!(
+        UserFeed,
+        r#"
+        SELECT id, owner, feed, title, icon as "icon:Icon", created_at, updated_at FROM user_feed
+        WHERE owner = $1
+        ORDER BY lower(title), id
+        "#,
+        user.id
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(subscriptions))
+}
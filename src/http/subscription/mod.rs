@@ -0,0 +1,24 @@
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use super::common::ApiContext;
+
+mod create;
+mod delete;
+mod export;
+mod get;
+mod import;
+mod list;
+mod update;
+
+pub fn router() -> OpenApiRouter<ApiContext> {
+    OpenApiRouter::new()
+        .routes(routes!(list::list_subscriptions, create::subscribe))
+        .routes(routes!(import::import_subscriptions))
+        .routes(routes!(export::export_subscriptions))
+        .routes(routes!(
+            get::get_subscription,
+            update::update_subscription,
+            delete::unsubscribe
+        ))
+}
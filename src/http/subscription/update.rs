@@ -0,0 +1,60 @@
+use crate::http::common::*;
+use crate::sql::{Icon, UserFeed};
+
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSubscriptionRequest {
+    /// Title to show for the feed instead of its own
+    #[validate(length(min = 1, max = 1024))]
+    title: Option<String>,
+    /// Icon to show for the feed instead of its website's
+    icon: Option<Icon>,
+}
+
+/// Update a subscription
+///
+/// Renames the feed or sets a custom icon for it, for the user only.
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    tag = "subscription",
+    params(
+        ("id" = i32, Path, description = "Subscription ID")
+    ),
+    request_body = UpdateSubscriptionRequest,
+    responses(
+        (status = 200, description = "Subscription updated successfully", body = UserFeed),
+        (status = 400, description = "Invalid subscription parameters"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the owner of the subscription"),
+        (status = 404, description = "Subscription not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn update_subscription(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Path(id): Path<i32>,
+    Json(body): Json<UpdateSubscriptionRequest>,
+) -> Result<Json<UserFeed>> {
+    body.validate()?;
+    let mut subscription = UserFeed::get(&state.pool, id).await?;
+    if subscription.owner != user.id {
+        return Err(Error::NotOwner);
+    }
+
+    if let Some(title) = body.title {
+        subscription.title = title;
+    }
+    if let Some(icon) = body.icon {
+        subscription.icon = Some(icon);
+    }
+
+    subscription.updated_at = chrono::Utc::now();
+    subscription.update(&state.pool).await?;
+
+    Ok(Json(subscription))
+}
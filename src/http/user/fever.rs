@@ -0,0 +1,65 @@
+use crate::http::common::*;
+use crate::sql::UserFeverKey;
+
+#[derive(Deserialize, Serialize, Validate, utoipa::ToSchema)]
+pub struct SetFeverKeyRequest {
+    /// Password to enter in Fever clients, along with the user's email
+    #[validate(length(min = 8, max = 256))]
+    password: String,
+}
+
+/// Set the Fever API key
+///
+/// Fever clients authenticate with md5("<email>:<password>"), computed from the user's email
+/// and this password. It should differ from the user's own password, since clients send it
+/// with a weak hash. Setting a new password replaces the previous key.
+#[utoipa::path(
+    put,
+    path = "/fever-key",
+    tag = "user",
+    request_body = SetFeverKeyRequest,
+    responses(
+        (status = 204, description = "Key set"),
+        (status = 400, description = "Invalid password"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn set_fever_key(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+    Json(body): Json<SetFeverKeyRequest>,
+) -> Result<impl IntoResponse> {
+    body.validate()?;
+
+    UserFeverKey::set(&state.pool, user.id, &user.email, &body.password).await?;
+    Ok(http::StatusCode::NO_CONTENT)
+}
+
+/// Revoke the Fever API key
+#[utoipa::path(
+    delete,
+    path = "/fever-key",
+    tag = "user",
+    responses(
+        (status = 204, description = "Key revoked"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "No key was set"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("BearerToken" = [])
+    )
+)]
+pub async fn revoke_fever_key(
+    user: AuthUser,
+    State(state): State<ApiContext>,
+) -> Result<impl IntoResponse> {
+    if !UserFeverKey::revoke(&state.pool, user.id).await? {
+        return Err(Error::NotFound("No Fever key was set".to_string()));
+    }
+    Ok(http::StatusCode::NO_CONTENT)
+}
@@ -1,16 +1,20 @@
 use crate::http::common::*;
-use crate::sql::HistoryItem;
+use crate::query::SqlParams;
+use crate::sql::{fetch_page, CursorPagination, HistoryItem, Page, RowOrder, SortKeyType};
 
 /// Get user's reading history
+///
+/// Returns the most recently updated history items first, paginated with cursors.
 #[utoipa::path(
     get,
     path = "/",
     tag = "user:history",
     params(
-        Pagination
+        CursorPagination
     ),
     responses(
-        (status = 200, description = "User's reading history", body = Vec<HistoryItem>),
+        (status = 200, description = "User's reading history", body = Page<HistoryItem>),
+        (status = 400, description = "Invalid pagination"),
         (status = 401, description = "Unauthorized"),
         (status = 500, description = "Internal server error")
     ),
@@ -21,24 +25,29 @@ use crate::sql::HistoryItem;
 pub async fn get_user_history(
     user: AuthUser,
     State(state): State<ApiContext>,
-    Query(pagination): Query<Pagination>,
-) -> Result<Json<Vec<HistoryItem>>> {
-    let query = sqlx::query_as!(
-        HistoryItem,
-        r#"
-        SELECT * FROM user_history_item
-        WHERE owner = $1
-        ORDER BY updated_at DESC
-        LIMIT $2 OFFSET $3
-        "#,
-        user.id,
-        pagination.limit,
-        ((pagination.page - 1) * pagination.limit).into(),
+    Query(pagination): Query<CursorPagination>,
+) -> Result<Json<Page<HistoryItem>>> {
+    let order = RowOrder::new("user_history_item.id", true)
+        .key("user_history_item.updated_at", SortKeyType::Timestamp);
+    pagination.validate_for(&order)?;
+
+    let mut params = SqlParams::new();
+    let condition = format!(
+        "user_history_item.owner = {}::integer",
+        params.push(user.id)
+    );
+    let page = fetch_page(
+        &state.pool,
+        "user_history_item.*",
+        "user_history_item",
+        &condition,
+        &order,
+        &pagination,
+        params,
     )
-    .fetch_all(&state.pool)
     .await?;
 
-    Ok(Json(query))
+    Ok(Json(page))
 }
 
 /// Get a specific history item
@@ -1,8 +1,10 @@
 use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
 
 use crate::http::common::ApiContext;
 
 mod email;
+mod fever;
 mod history;
 mod oauth;
 
@@ -11,4 +13,5 @@ pub fn router() -> OpenApiRouter<ApiContext> {
         .nest("/oauth", oauth::router())
         .nest("/history", history::router())
         .nest("/email", email::router())
+        .routes(routes!(fever::set_fever_key, fever::revoke_fever_key))
 }
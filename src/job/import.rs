@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use crate::feed::daemon::FeedCreationError;
+use crate::feed::opml::Opml;
+use crate::sql::{UserFeed, UserTag};
+
+use super::{JobContext, Permanent};
+
+/// Subscribes the owner to the feeds of the OPML file one by one, recording the progress of
+/// the import, and then adds the feeds of each top-level folder to a tag. Feeds which can't be
+/// fetched don't fail the import, and are listed in the `failed` field of the result instead.
+///
+/// Subscribing and adding feeds to tags are idempotent, so a retried import picks up the feeds
+/// which were subscribed to before it failed.
+pub(super) async fn import_opml(
+    ctx: &JobContext<'_>,
+    opml: &str,
+) -> anyhow::Result<serde_json::Value> {
+    let owner = ctx.owner()?;
+    let opml = Opml::parse(opml).map_err(|err| Permanent(err.into()))?;
+
+    let mut links = opml
+        .outlines
+        .iter()
+        .flat_map(|outline| outline.feeds())
+        .filter_map(|feed| feed.xml_url.clone())
+        .collect::<Vec<_>>();
+    links.sort();
+    links.dedup();
+    let total = Some(links.len() as i32);
+    ctx.progress(0, total).await?;
+
+    let mut feed_ids = HashMap::new();
+    let mut failed = Vec::new();
+    for outline in opml.outlines.iter().flat_map(|outline| outline.feeds()) {
+        let Some(link) = &outline.xml_url else {
+            continue;
+        };
+        if feed_ids.contains_key(link) || failed.contains(link) {
+            continue;
+        }
+
+        let title = Some(outline.title.clone()).filter(|title| !title.is_empty());
+        match UserFeed::subscribe(ctx.pool, owner, link, title, None).await {
+            Ok((subscription, _)) => {
+                feed_ids.insert(link.clone(), subscription.feed);
+            }
+            // Database errors fail the import so it gets retried
+            Err(FeedCreationError::SqlxError(err)) => return Err(err.into()),
+            Err(err) => {
+                tracing::warn!("Failed to import feed {}: {:?}", link, err);
+                failed.push(link.clone());
+            }
+        }
+        ctx.progress((feed_ids.len() + failed.len()) as i32, total)
+            .await?;
+    }
+
+    for folder in opml
+        .outlines
+        .iter()
+        .filter(|outline| outline.xml_url.is_none())
+    {
+        let feeds = folder
+            .feeds()
+            .iter()
+            .filter_map(|feed| feed.xml_url.as_ref().and_then(|link| feed_ids.get(link)))
+            .copied()
+            .collect::<Vec<_>>();
+        if feeds.is_empty() || folder.title.is_empty() {
+            continue;
+        }
+        UserTag::add_feeds(ctx.pool, owner, &folder.title, &feeds).await?;
+    }
+
+    Ok(serde_json::json!({ "failed": failed }))
+}
@@ -0,0 +1,106 @@
+//! Background jobs, queued in the `job` table by the API and run by the workers of the daemon.
+//!
+//! Each kind of job is a variant of [`JobKind`], whose fields are stored as the payload of the
+//! job. Jobs report their progress as they go, return a JSON result when they complete, and
+//! are retried with backoff when they fail, unless their error is [`Permanent`].
+
+mod import;
+mod worker;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+pub use worker::JobWorkers;
+
+use crate::sql::Job;
+
+/// The kinds of jobs, with their parameters
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "payload", rename_all = "snake_case")]
+pub enum JobKind {
+    /// Subscribes the owner to the feeds of an OPML file and turns its folders into tags
+    ImportOpml { opml: String },
+}
+
+impl JobKind {
+    /// Splits the job into the name of its kind and its payload, as stored in the database
+    pub fn to_parts(&self) -> (String, serde_json::Value) {
+        let mut value = serde_json::to_value(self).expect("job kinds serialize to JSON");
+        let name = value["kind"].as_str().unwrap_or_default().to_string();
+        (name, value["payload"].take())
+    }
+
+    pub fn from_parts(name: &str, payload: serde_json::Value) -> serde_json::Result<Self> {
+        serde_json::from_value(serde_json::json!({ "kind": name, "payload": payload }))
+    }
+}
+
+/// Error of a job which would fail the same way if retried, such as an invalid payload, so the
+/// job fails on the attempt instead of being retried
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct Permanent(#[from] pub anyhow::Error);
+
+/// What a running job can access
+pub struct JobContext<'a> {
+    pub pool: &'a PgPool,
+    pub job: &'a Job,
+}
+
+impl JobContext<'_> {
+    pub fn owner(&self) -> anyhow::Result<i32> {
+        self.job.owner.context("Job has no owner")
+    }
+
+    pub async fn progress(&self, progress: i32, total: Option<i32>) -> sqlx::Result<()> {
+        Job::set_progress(self.pool, self.job.id, progress, total).await
+    }
+}
+
+async fn run(ctx: &JobContext<'_>, kind: JobKind) -> anyhow::Result<serde_json::Value> {
+    match kind {
+        JobKind::ImportOpml { opml } => import::import_opml(ctx, &opml).await,
+    }
+}
+
+/// Claims the next job which is due and runs it, recording its result or failure. Returns
+/// whether there was a job to run
+pub async fn run_next(pool: &PgPool) -> anyhow::Result<bool> {
+    Job::fail_stale(pool).await?;
+    let Some(job) = Job::claim(pool).await? else {
+        return Ok(false);
+    };
+
+    let ctx = JobContext { pool, job: &job };
+    let result = match JobKind::from_parts(&job.kind, job.payload.clone()) {
+        Ok(kind) => run(&ctx, kind).await,
+        Err(err) => Err(Permanent(anyhow::Error::new(err).context("Invalid job payload")).into()),
+    };
+
+    match result {
+        Ok(result) => Job::complete(pool, job.id, result).await?,
+        Err(err) => {
+            tracing::warn!("Job {} ({}) failed: {:?}", job.id, job.kind, err);
+            let retry = err.downcast_ref::<Permanent>().is_none();
+            job.fail(pool, &format!("{:#}", err), retry).await?;
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trips_kinds() {
+        let kind = JobKind::ImportOpml {
+            opml: "<opml/>".to_string(),
+        };
+        let (name, payload) = kind.to_parts();
+        assert_eq!(name, "import_opml");
+        assert_eq!(payload, serde_json::json!({ "opml": "<opml/>" }));
+        assert_eq!(JobKind::from_parts(&name, payload).unwrap(), kind);
+    }
+}
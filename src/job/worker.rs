@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tokio::{sync::watch, task::JoinHandle};
+
+/// How long idle workers wait before checking the queue again
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Pool of workers which run the jobs of the queue concurrently
+pub struct JobWorkers {
+    tasks: Vec<JoinHandle<()>>,
+    cancel_tx: watch::Sender<bool>,
+}
+
+impl JobWorkers {
+    pub fn start(pool: PgPool, count: usize) -> Self {
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+
+        let tasks = (0..count)
+            .map(|_| {
+                let pool = pool.clone();
+                let mut cancel_rx = cancel_rx.clone();
+                tokio::spawn(async move {
+                    loop {
+                        // Jobs interrupted by cancelling are claimed again once they are stale
+                        let ran = tokio::select! {
+                            _ = cancel_rx.changed() => break,
+                            ran = super::run_next(&pool) => ran,
+                        };
+
+                        match ran {
+                            // Keep going while there are jobs due
+                            Ok(true) => continue,
+                            Ok(false) => {}
+                            Err(err) => tracing::error!("Error while running job: {:?}", err),
+                        }
+
+                        tokio::select! {
+                            _ = cancel_rx.changed() => break,
+                            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        Self { tasks, cancel_tx }
+    }
+
+    pub async fn cancel(self) {
+        self.cancel_tx.send(true).ok();
+        for task in self.tasks {
+            if let Err(err) = task.await {
+                tracing::error!("Error while canceling job worker: {:?}", err);
+            }
+        }
+    }
+}
@@ -3,6 +3,7 @@ pub mod config;
 pub mod constants;
 pub mod feed;
 pub mod http;
+pub mod job;
 pub mod query;
 pub mod scraper;
 pub mod smtp;
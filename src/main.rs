@@ -59,6 +59,7 @@ async fn main() {
         DaemonOptions {
             concurrent_updates: 5,
             lease_name: config.leader_election_lease_name,
+            job_workers: 4,
         },
     )
     .await
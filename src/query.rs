@@ -1,3 +1,5 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use nom::combinator::map_opt;
 use nom::error::ErrorKind;
 use nom::sequence::{separated_pair, terminated};
 use nom::Parser;
@@ -9,25 +11,135 @@ use nom::{
     sequence::{delimited, preceded},
     IResult,
 };
-use validator::ValidationError;
+use serde::Serialize;
+use utoipa::ToSchema;
 
-#[derive(PartialEq, Debug, Clone)]
+/// Expression of a search query, serialized as `{ "type": "word", "value": "rust" }`
+#[derive(PartialEq, Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
 pub enum SearchExpr {
     Phrase(String),
     Word(String),
     BinaryOp(BinaryOperator, Box<SearchExpr>, Box<SearchExpr>),
     Not(Box<SearchExpr>),
-    Field(String, Box<SearchExpr>),
+    Field(SearchField, Box<SearchExpr>),
     IsRead,
     IsUnread,
+    IsStarred,
     Group(Vec<SearchExpr>),
 }
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum BinaryOperator {
     And,
     Or,
 }
 
+/// Fields which can be searched on with `field:value`
+#[derive(PartialEq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchField {
+    /// Items from the feed with the id
+    Feed,
+    /// Items from the feeds in one of the user's tags, by title or id
+    Tag,
+    /// Items with the words in their title
+    Title,
+    /// Items whose author contains the text
+    Author,
+    /// Items in the category
+    Category,
+    /// Items from feeds on the domain or its subdomains
+    Domain,
+    /// Items from feeds in the language, as an ISO 639-1 code
+    Lang,
+    /// Items with an attachment, currently only `has:enclosure`
+    Has,
+    /// Items published before the date
+    Before,
+    /// Items published on or after the date
+    After,
+    /// Items in one of the user's lists, by title or id
+    List,
+}
+
+impl SearchField {
+    pub const ALL: [SearchField; 11] = [
+        SearchField::Feed,
+        SearchField::Tag,
+        SearchField::Title,
+        SearchField::Author,
+        SearchField::Category,
+        SearchField::Domain,
+        SearchField::Lang,
+        SearchField::Has,
+        SearchField::Before,
+        SearchField::After,
+        SearchField::List,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SearchField::Feed => "feed",
+            SearchField::Tag => "tag",
+            SearchField::Title => "title",
+            SearchField::Author => "author",
+            SearchField::Category => "category",
+            SearchField::Domain => "domain",
+            SearchField::Lang => "lang",
+            SearchField::Has => "has",
+            SearchField::Before => "before",
+            SearchField::After => "after",
+            SearchField::List => "list",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|field| field.name() == name)
+    }
+
+    /// Checks that the value can be used with the field, returning why it can't otherwise
+    pub fn validate_value(&self, value: &str) -> Result<(), String> {
+        match self {
+            SearchField::Feed if value.parse::<i32>().is_err() => {
+                Err(format!("feed must be the id of a feed, got \"{}\"", value))
+            }
+            SearchField::Lang
+                if value.len() != 2 || !value.chars().all(|c| c.is_ascii_alphabetic()) =>
+            {
+                Err(format!(
+                    "lang must be a two letter language code (e.g. en), got \"{}\"",
+                    value
+                ))
+            }
+            SearchField::Has if value != "enclosure" => {
+                Err(format!("has only supports enclosure, got \"{}\"", value))
+            }
+            SearchField::Before | SearchField::After if parse_date(value).is_none() => {
+                Err(format!(
+                    "{} must be a date (e.g. 2025-01-31), got \"{}\"",
+                    self.name(),
+                    value
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parses a date (`2025-01-31`) as midnight UTC, or a full RFC 3339 timestamp
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc())
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(value)
+                .ok()
+                .map(|date| date.to_utc())
+        })
+}
+
 #[derive(Debug)]
 pub struct Query {
     pub exprs: Vec<SearchExpr>,
@@ -44,27 +156,104 @@ fn parse_phrase(input: &str) -> IResult<&str, SearchExpr> {
     Ok((input, SearchExpr::Phrase(phrase.to_string())))
 }
 
-fn parse_field(input: &str) -> IResult<&str, SearchExpr> {
-    let (input, field) = separated_pair(
+fn parse_field_name(input: &str) -> IResult<&str, SearchField> {
+    map_opt(
         take_while1(|c: char| c.is_alphanumeric()),
-        char(':'),
-        alt((parse_group, parse_word, parse_phrase)),
+        SearchField::from_name,
+    )
+    .parse(input)
+}
+
+/// Field values may contain any character other than whitespace, parentheses and quotes so
+/// that domains (`example.com`) and dates (`2025-01-31`) don't need to be quoted
+fn parse_field_word(input: &str) -> IResult<&str, SearchExpr> {
+    let (input, word) =
+        take_while1(|c: char| !c.is_whitespace() && !matches!(c, '(' | ')' | '"'))(input)?;
+    Ok((input, SearchExpr::Word(word.to_string())))
+}
+
+fn parse_field_group(input: &str) -> IResult<&str, SearchExpr> {
+    let (input, exprs) = delimited(
+        char('('),
+        separated_list1(
+            space1,
+            alt((
+                parse_field_group,
+                parse_field_binary_op,
+                parse_phrase,
+                parse_field_word,
+            )),
+        ),
+        char(')'),
+    )
+    .parse(input)?;
+    Ok((input, SearchExpr::Group(exprs)))
+}
+
+fn parse_field_binary_op(input: &str) -> IResult<&str, SearchExpr> {
+    let (input, (left, (operator, right))) = separated_pair(
+        alt((parse_field_group, parse_phrase, parse_field_word)),
+        space1,
+        separated_pair(
+            parse_binary_operator,
+            space1,
+            alt((parse_field_group, parse_phrase, parse_field_word)),
+        ),
     )
     .parse(input)?;
 
     Ok((
         input,
-        SearchExpr::Field(field.0.to_string(), Box::new(field.1)),
+        SearchExpr::BinaryOp(operator, Box::new(left), Box::new(right)),
     ))
 }
 
+/// Checks every word and phrase of a field's value with [SearchField::validate_value]
+fn validate_field_value(field: SearchField, expr: &SearchExpr) -> Result<(), String> {
+    match expr {
+        SearchExpr::Word(value) | SearchExpr::Phrase(value) => field.validate_value(value),
+        SearchExpr::Group(exprs) => exprs
+            .iter()
+            .try_for_each(|expr| validate_field_value(field, expr)),
+        SearchExpr::BinaryOp(_, left, right) => {
+            validate_field_value(field, left)?;
+            validate_field_value(field, right)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn parse_field(input: &str) -> IResult<&str, SearchExpr> {
+    let (rest, (field, value)) = separated_pair(
+        parse_field_name,
+        char(':'),
+        alt((parse_field_group, parse_phrase, parse_field_word)),
+    )
+    .parse(input)?;
+
+    // The field is known at this point, so fail instead of trying to parse it as a word
+    if validate_field_value(field, &value).is_err() {
+        return Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            ErrorKind::Verify,
+        )));
+    }
+
+    Ok((rest, SearchExpr::Field(field, Box::new(value))))
+}
+
 fn parse_is(input: &str) -> IResult<&str, SearchExpr> {
-    let (input, (_, value)) =
-        separated_pair(tag("is"), char(':'), alt((tag("read"), tag("unread")))).parse(input)?;
+    let (input, (_, value)) = separated_pair(
+        tag("is"),
+        char(':'),
+        alt((tag("read"), tag("unread"), tag("starred"))),
+    )
+    .parse(input)?;
 
     match value {
         "read" => Ok((input, SearchExpr::IsRead)),
         "unread" => Ok((input, SearchExpr::IsUnread)),
+        "starred" => Ok((input, SearchExpr::IsStarred)),
         _ => unreachable!(),
     }
 }
@@ -134,129 +323,631 @@ fn parse_expr(input: &str) -> IResult<&str, SearchExpr> {
     .parse(input)
 }
 
-pub fn parse_query(input: &str) -> IResult<&str, Query> {
-    let (input, exprs) = separated_list1(space1, parse_expr).parse(input)?;
-    // Ensure that the entire input is consumed
-    if !input.trim().is_empty() {
-        return Err(nom::Err::Error(nom::error::Error::new(
-            input,
-            ErrorKind::Complete,
-        )));
-    }
-    Ok((input, Query { exprs }))
+/// Error returned when a query can't be parsed, pointing at the part of the query which is
+/// invalid so that clients can highlight it
+#[derive(Debug, Clone, PartialEq, Serialize, thiserror::Error, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[error("{message}")]
+pub struct QueryError {
+    /// Byte offset in the query at which the error is
+    pub offset: usize,
+    /// Tokens which would have been valid at the offset
+    pub expected: Vec<String>,
+    /// Likely replacements for the invalid token, e.g. the closest field name
+    pub suggestions: Vec<String>,
+    /// Human readable description of the error
+    pub message: String,
 }
 
-pub fn validate_query(query: &str) -> Result<(), ValidationError> {
-    match parse_query(query) {
-        Ok(_) => Ok(()),
-        Err(_) => Err(ValidationError::new("Invalid query")),
+impl QueryError {
+    fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            expected: vec![],
+            suggestions: vec![],
+            message: message.into(),
+        }
+    }
+
+    fn expected(mut self, expected: &[&str]) -> Self {
+        self.expected = expected.iter().map(|token| token.to_string()).collect();
+        self
+    }
+
+    fn suggestions(mut self, suggestions: Vec<String>) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+
+    /// Moves the error by the offset, for errors found in a part of a larger query
+    fn shift(mut self, offset: usize) -> Self {
+        self.offset += offset;
+        self
     }
 }
 
-impl TryFrom<String> for Query {
-    type Error = ValidationError;
+/// Tokens which can start an expression
+const EXPR_TOKENS: &[&str] = &[
+    "word",
+    "\"phrase\"",
+    "(group)",
+    "NOT",
+    "field:value",
+    "is:read",
+    "is:unread",
+    "is:starred",
+];
+/// Tokens which can be used inside of groups
+const GROUP_TOKENS: &[&str] = &["word", "\"phrase\"", "(group)", "AND", "OR"];
+/// Tokens which can be used as the value of a field
+const FIELD_VALUE_TOKENS: &[&str] = &["value", "\"phrase\"", "(group)"];
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        match parse_query(&value) {
-            Ok((_, query)) => Ok(query),
-            Err(_) => Err(ValidationError::new("Invalid query")),
+fn is_space(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+pub fn parse_query(input: &str) -> Result<Query, QueryError> {
+    let mut exprs = vec![];
+    let mut rest = input.trim_start_matches(is_space);
+    while !rest.is_empty() {
+        let offset = input.len() - rest.len();
+        let (next, expr) = parse_expr(rest).map_err(|_| diagnose(rest).shift(offset))?;
+        exprs.push(expr);
+
+        // Expressions must be separated by spaces
+        let trimmed = next.trim_start_matches(is_space);
+        if !next.is_empty() && trimmed.len() == next.len() {
+            let error = if next.starts_with(':') {
+                // An unknown field or a field without a value, which was parsed as a word
+                let consumed = &rest[..rest.len() - next.len()];
+                let word = consumed
+                    .trim_end_matches(|c: char| c.is_alphanumeric())
+                    .len();
+                diagnose(&rest[word..]).shift(offset + word)
+            } else if next.starts_with(|c: char| c.is_alphanumeric() || matches!(c, '(' | '"')) {
+                QueryError::new(
+                    input.len() - next.len(),
+                    "Expected a space between expressions",
+                )
+                .expected(&["space"])
+            } else {
+                diagnose(next).shift(input.len() - next.len())
+            };
+            return Err(error);
         }
+        rest = trimmed;
+    }
+
+    if exprs.is_empty() {
+        return Err(QueryError::new(0, "Query is empty").expected(EXPR_TOKENS));
     }
+    Ok(Query { exprs })
 }
 
-fn expr_to_sql(expr: &SearchExpr, params: &mut Vec<String>) -> (String, Vec<String>) {
-    match expr {
-        SearchExpr::Word(word) => {
-            params.push(format!("%{}%", word)); // Add parameter
-            ("content ILIKE ?".to_string(), params.clone()) // Use ? as a placeholder
+/// Works out why an expression at the start of the input couldn't be parsed, with the offset
+/// of the error relative to the input
+fn diagnose(input: &str) -> QueryError {
+    match input.chars().next() {
+        Some('(') => diagnose_group(input),
+        Some(')') => QueryError::new(0, "Unexpected `)` without a matching `(`"),
+        Some('"') if !input[1..].contains('"') => {
+            QueryError::new(0, "Phrase is never closed").expected(&["\""])
         }
-        SearchExpr::Phrase(phrase) => {
-            params.push(phrase.clone()); // Add parameter
-            ("content = ?".to_string(), params.clone()) // Use ? as a placeholder
+        Some('"') => QueryError::new(0, "Phrases can't be empty").expected(&["phrase"]),
+        Some(c) if c.is_alphanumeric() => diagnose_field(input),
+        Some(c) => {
+            QueryError::new(0, format!("Unexpected character `{}`", c)).expected(EXPR_TOKENS)
         }
-        SearchExpr::BinaryOp(op, left, right) => {
-            let (left_sql, left_params) = expr_to_sql(left, params);
-            let (right_sql, right_params) = expr_to_sql(right, &mut left_params.clone());
-            let operator = match op {
-                BinaryOperator::And => "AND",
-                BinaryOperator::Or => "OR",
+        None => QueryError::new(0, "Unexpected end of query").expected(EXPR_TOKENS),
+    }
+}
+
+fn diagnose_group(input: &str) -> QueryError {
+    let Some(end) = closing_paren(input) else {
+        return QueryError::new(0, "Group is never closed").expected(&[")"]);
+    };
+
+    let inner = &input[1..end];
+    if inner.trim_matches(is_space).is_empty() {
+        return QueryError::new(1, "Groups can't be empty").expected(GROUP_TOKENS);
+    }
+    if inner.starts_with(is_space) || inner.ends_with(is_space) {
+        return QueryError::new(1, "Groups can't start or end with a space");
+    }
+    match parse_query(inner) {
+        Err(error) => error.shift(1),
+        Ok(_) => QueryError::new(
+            0,
+            "Groups can only contain words, phrases and other groups, joined with AND or OR",
+        )
+        .expected(GROUP_TOKENS),
+    }
+}
+
+/// Returns the position of the parenthesis which closes the group at the start of the input
+fn closing_paren(input: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_phrase = false;
+    for (i, c) in input.char_indices() {
+        match c {
+            '"' => in_phrase = !in_phrase,
+            '(' if !in_phrase => depth += 1,
+            ')' if !in_phrase => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn diagnose_field(input: &str) -> QueryError {
+    let name_len = input
+        .find(|c: char| !c.is_alphanumeric())
+        .unwrap_or(input.len());
+    let (name, rest) = input.split_at(name_len);
+    let Some(value) = rest.strip_prefix(':') else {
+        return match rest.chars().next() {
+            Some(c) => QueryError::new(name_len, format!("Unexpected character `{}`", c))
+                .expected(&["space"]),
+            None => QueryError::new(name_len, "Unexpected end of query").expected(EXPR_TOKENS),
+        };
+    };
+    let offset = name_len + 1;
+
+    if name == "is" {
+        let token = value.split(is_space).next().unwrap_or_default();
+        return QueryError::new(offset, format!("Unknown value `{}` for is", token))
+            .expected(&["read", "unread", "starred"])
+            .suggestions(closest(token, ["read", "unread", "starred"]));
+    }
+
+    let Some(field) = SearchField::from_name(name) else {
+        let mut names: Vec<&str> = SearchField::ALL.iter().map(|field| field.name()).collect();
+        names.push("is");
+        let suggestions = closest(name, names.iter().copied());
+        let message = match suggestions.first() {
+            Some(suggestion) => format!("Unknown field `{}`, did you mean `{}`?", name, suggestion),
+            None => format!("Unknown field `{}`", name),
+        };
+        return QueryError::new(0, message)
+            .expected(&names)
+            .suggestions(suggestions);
+    };
+
+    if value.is_empty() || value.starts_with(is_space) {
+        return QueryError::new(offset, format!("Expected a value after `{}:`", name))
+            .expected(FIELD_VALUE_TOKENS);
+    }
+    match alt((parse_field_group, parse_phrase, parse_field_word)).parse(value) {
+        Ok((_, expr)) => match validate_field_value(field, &expr) {
+            Err(message) => QueryError::new(offset, message),
+            Ok(()) => QueryError::new(offset, format!("Invalid value for `{}:`", name))
+                .expected(FIELD_VALUE_TOKENS),
+        },
+        Err(_) => diagnose(value).shift(offset),
+    }
+}
+
+/// Returns the candidates which are a couple of typos away from the value, closest first
+fn closest<'a>(value: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut matches: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(value, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .collect();
+    matches.sort();
+    matches
+        .into_iter()
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if a_char == *b_char {
+                diagonal
+            } else {
+                1 + diagonal.min(row[j]).min(above)
             };
-            (
-                format!("({} {} {})", left_sql, operator, right_sql),
-                right_params,
-            )
+            diagonal = above;
         }
-        SearchExpr::Not(expr) => {
-            let (expr_sql, expr_params) = expr_to_sql(expr, params);
-            (format!("NOT ({})", expr_sql), expr_params)
+    }
+    row[b.len()]
+}
+
+impl TryFrom<String> for Query {
+    type Error = QueryError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        parse_query(&value)
+    }
+}
+
+/// Parameters of a dynamically built SQL query, handing out `$n` placeholders in the order
+/// they're pushed so that callers can add their own parameters before or after the query's
+#[derive(Debug, Default)]
+pub struct SqlParams(Vec<String>);
+
+impl SqlParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a parameter, returning the placeholder which references it
+    pub fn push(&mut self, value: impl ToString) -> String {
+        self.0.push(value.to_string());
+        format!("${}", self.0.len())
+    }
+
+    pub fn into_inner(self) -> Vec<String> {
+        self.0
+    }
+}
+
+/// Compiles search expressions to Postgres predicates over `feed_item` for a single user,
+/// since some predicates (e.g. `is:read`) depend on who is searching
+struct SqlCompiler<'a> {
+    user_id: i32,
+    user_param: Option<String>,
+    params: &'a mut SqlParams,
+}
+
+impl SqlCompiler<'_> {
+    /// Placeholder of the user's id, only added to the parameters when it's first used
+    fn user(&mut self) -> String {
+        let user_id = self.user_id;
+        let params = &mut self.params;
+        self.user_param
+            .get_or_insert_with(|| format!("{}::integer", params.push(user_id)))
+            .clone()
+    }
+
+    /// Matches items whose title, description or content contains the words
+    fn text(&mut self, text: &str) -> String {
+        format!(
+            "feed_item.search_vector @@ websearch_to_tsquery('english', {})",
+            self.params.push(text)
+        )
+    }
+
+    /// Matches items whose title, description or content contains the words in the same order
+    fn phrase(&mut self, phrase: &str) -> String {
+        format!(
+            "feed_item.search_vector @@ phraseto_tsquery('english', {})",
+            self.params.push(phrase)
+        )
+    }
+
+    /// Matches items whose state for the user has the boolean column set, e.g. `read`
+    fn item_state(&mut self, column: &str) -> String {
+        format!(
+            "EXISTS (SELECT 1 FROM user_item_state \
+            WHERE user_item_state.item = feed_item.id AND user_item_state.owner = {} \
+            AND user_item_state.{})",
+            self.user(),
+            column
+        )
+    }
+
+    fn field(&mut self, field: SearchField, expr: &SearchExpr) -> String {
+        match expr {
+            SearchExpr::Word(value) => self.field_value(field, value, false),
+            SearchExpr::Phrase(value) => self.field_value(field, value, true),
+            SearchExpr::Group(exprs) => {
+                let mut conditions: Vec<String> =
+                    exprs.iter().map(|expr| self.field(field, expr)).collect();
+                match conditions.len() {
+                    1 => conditions.remove(0),
+                    _ => format!("({})", conditions.join(" AND ")),
+                }
+            }
+            SearchExpr::BinaryOp(op, left, right) => {
+                let left_sql = self.field(field, left);
+                let right_sql = self.field(field, right);
+                format!("({} {} {})", left_sql, op.to_sql(), right_sql)
+            }
+            SearchExpr::Not(expr) => format!("NOT ({})", self.field(field, expr)),
+            SearchExpr::Field(field, expr) => self.field(*field, expr),
+            SearchExpr::IsRead => self.item_state("read"),
+            SearchExpr::IsUnread => format!("NOT {}", self.item_state("read")),
+            SearchExpr::IsStarred => self.item_state("starred"),
         }
-        SearchExpr::Field(field, expr) => match expr.as_ref() {
-            SearchExpr::Word(word) | SearchExpr::Phrase(word) => {
-                params.push(word.clone());
-                (format!("{} = ?", field), params.clone())
+    }
+
+    /// Matches items by a single value of a field, which has already been checked by
+    /// [SearchField::validate_value]
+    fn field_value(&mut self, field: SearchField, value: &str, phrase: bool) -> String {
+        match field {
+            SearchField::Feed => {
+                format!("feed_item.feed_id = {}::integer", self.params.push(value))
+            }
+            SearchField::Tag => {
+                let user = self.user();
+                let value = self.params.push(value);
+                format!(
+                    "feed_item.feed_id IN (SELECT (child).id \
+                    FROM user_tag, unnest(user_tag.children) AS child \
+                    WHERE user_tag.owner = {} AND (child).type = 'feed' \
+                    AND (user_tag.id::text = {} OR lower(user_tag.title) = lower({})))",
+                    user, value, value
+                )
             }
+            SearchField::Title => format!(
+                "to_tsvector('english', feed_item.title) @@ {}('english', {})",
+                if phrase {
+                    "phraseto_tsquery"
+                } else {
+                    "websearch_to_tsquery"
+                },
+                self.params.push(value)
+            ),
+            SearchField::Author => format!(
+                "position(lower({}) in lower(feed_item.author)) > 0",
+                self.params.push(value)
+            ),
+            SearchField::Category => format!(
+                "EXISTS (SELECT 1 FROM unnest(feed_item.categories) AS category \
+                WHERE lower(category) = lower({}))",
+                self.params.push(value)
+            ),
+            SearchField::Domain => {
+                let value = self.params.push(value.to_lowercase());
+                format!(
+                    "EXISTS (SELECT 1 FROM feed WHERE feed.id = feed_item.feed_id \
+                    AND (lower(feed.domain) = {} \
+                    OR right(lower(feed.domain), length({}) + 1) = '.' || {}))",
+                    value, value, value
+                )
+            }
+            SearchField::Lang => format!(
+                "EXISTS (SELECT 1 FROM feed WHERE feed.id = feed_item.feed_id \
+                AND feed.language = {})",
+                self.params.push(value.to_lowercase())
+            ),
+            SearchField::Has => "feed_item.enclosure IS NOT NULL".to_string(),
+            SearchField::Before | SearchField::After => {
+                let date = parse_date(value)
+                    .map(|date| date.to_rfc3339())
+                    .unwrap_or_else(|| value.to_string());
+                format!(
+                    "COALESCE(feed_item.published_at, feed_item.created_at) {} {}::timestamptz",
+                    if field == SearchField::Before {
+                        "<"
+                    } else {
+                        ">="
+                    },
+                    self.params.push(date)
+                )
+            }
+            SearchField::List => {
+                let user = self.user();
+                let value = self.params.push(value);
+                format!(
+                    "feed_item.id IN (SELECT user_list_item.item FROM user_list_item \
+                    JOIN user_list ON user_list.id = user_list_item.list \
+                    WHERE user_list.owner = {} \
+                    AND (user_list.id::text = {} OR lower(user_list.title) = lower({})))",
+                    user, value, value
+                )
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &SearchExpr) -> String {
+        match expr {
+            SearchExpr::Word(word) => self.text(word),
+            SearchExpr::Phrase(phrase) => self.phrase(phrase),
+            SearchExpr::BinaryOp(op, left, right) => {
+                let left_sql = self.expr(left);
+                let right_sql = self.expr(right);
+                format!("({} {} {})", left_sql, op.to_sql(), right_sql)
+            }
+            SearchExpr::Not(expr) => format!("NOT ({})", self.expr(expr)),
+            SearchExpr::Field(field, expr) => self.field(*field, expr),
+            SearchExpr::IsRead => self.item_state("read"),
+            SearchExpr::IsUnread => format!("NOT {}", self.item_state("read")),
+            SearchExpr::IsStarred => self.item_state("starred"),
             SearchExpr::Group(exprs) => {
-                let mut group_params = params.clone();
-                let conditions: Vec<String> = exprs
-                    .iter()
-                    .map(|expr| {
-                        let (sql, new_params) = expr_to_sql(
-                            &SearchExpr::Field(field.clone(), Box::new(expr.clone())),
-                            &mut group_params,
-                        );
-                        group_params = new_params;
-                        sql
-                    })
-                    .collect();
-                (format!("({})", conditions.join(" AND ")), group_params)
+                let conditions: Vec<String> = exprs.iter().map(|expr| self.expr(expr)).collect();
+                format!("({})", conditions.join(" AND "))
             }
-            SearchExpr::BinaryOp(op, expr_a, expr_b) if op == &BinaryOperator::Or => {
-                let (expr_a_sql, expr_a_params) =
-                    expr_to_sql(&SearchExpr::Field(field.clone(), expr_a.clone()), params);
-                let (expr_b_sql, expr_b_params) = expr_to_sql(
-                    &SearchExpr::Field(field.clone(), expr_b.clone()),
-                    &mut expr_a_params.clone(),
-                );
-                (format!("{} OR {}", expr_a_sql, expr_b_sql), expr_b_params)
+        }
+    }
+}
+
+/// Collects the words and phrases which contribute to the relevance of an item, skipping
+/// negated terms and fields since they don't say anything about what the user is looking for
+fn collect_text_terms(expr: &SearchExpr, terms: &mut Vec<String>) {
+    match expr {
+        SearchExpr::Word(word) => terms.push(word.clone()),
+        SearchExpr::Phrase(phrase) => terms.push(format!("\"{}\"", phrase)),
+        SearchExpr::BinaryOp(_, left, right) => {
+            collect_text_terms(left, terms);
+            collect_text_terms(right, terms);
+        }
+        SearchExpr::Group(exprs) => exprs
+            .iter()
+            .for_each(|expr| collect_text_terms(expr, terms)),
+        SearchExpr::Not(_)
+        | SearchExpr::Field(_, _)
+        | SearchExpr::IsRead
+        | SearchExpr::IsUnread
+        | SearchExpr::IsStarred => {}
+    }
+}
+
+impl BinaryOperator {
+    fn to_sql(&self) -> &'static str {
+        match self {
+            BinaryOperator::And => "AND",
+            BinaryOperator::Or => "OR",
+        }
+    }
+}
+
+/// Normalizes the expressions of a group, which are all required to match, by moving the
+/// expressions of nested groups into it
+fn normalize_group(exprs: Vec<SearchExpr>) -> Vec<SearchExpr> {
+    exprs
+        .into_iter()
+        .map(normalize_expr)
+        .flat_map(|expr| match expr {
+            SearchExpr::Group(exprs) => exprs,
+            expr => vec![expr],
+        })
+        .collect()
+}
+
+fn normalize_expr(expr: SearchExpr) -> SearchExpr {
+    match expr {
+        SearchExpr::Group(exprs) => {
+            let mut exprs = normalize_group(exprs);
+            if exprs.len() == 1 {
+                exprs.remove(0)
+            } else {
+                SearchExpr::Group(exprs)
             }
-            _ => unreachable!("Fields can only have groups, words or phrases"),
+        }
+        SearchExpr::BinaryOp(op, left, right) => SearchExpr::BinaryOp(
+            op,
+            Box::new(normalize_expr(*left)),
+            Box::new(normalize_expr(*right)),
+        ),
+        SearchExpr::Not(expr) => match normalize_expr(*expr) {
+            SearchExpr::Not(expr) => *expr,
+            expr => SearchExpr::Not(Box::new(expr)),
         },
-        SearchExpr::IsRead => {
-            unimplemented!()
+        SearchExpr::Field(field, value) => {
+            SearchExpr::Field(field, Box::new(normalize_expr(*value)))
         }
-        SearchExpr::IsUnread => {
-            unimplemented!()
+        expr => expr,
+    }
+}
+
+/// Collects the words and phrases used as values of the field
+fn collect_field_values(expr: &SearchExpr, field: SearchField, values: &mut Vec<String>) {
+    match expr {
+        SearchExpr::Field(expr_field, value) if *expr_field == field => {
+            collect_values(value, values)
         }
-        SearchExpr::Group(exprs) => {
-            let mut group_params = params.clone();
-            let conditions: Vec<String> = exprs
-                .iter()
-                .map(|expr| {
-                    let (sql, new_params) = expr_to_sql(expr, &mut group_params);
-                    group_params = new_params;
-                    sql
-                })
-                .collect();
-            (format!("({})", conditions.join(" AND ")), group_params)
+        SearchExpr::Field(_, _) | SearchExpr::Word(_) | SearchExpr::Phrase(_) => {}
+        SearchExpr::BinaryOp(_, left, right) => {
+            collect_field_values(left, field, values);
+            collect_field_values(right, field, values);
+        }
+        SearchExpr::Not(expr) => collect_field_values(expr, field, values),
+        SearchExpr::Group(exprs) => exprs
+            .iter()
+            .for_each(|expr| collect_field_values(expr, field, values)),
+        SearchExpr::IsRead | SearchExpr::IsUnread | SearchExpr::IsStarred => {}
+    }
+}
+
+fn collect_values(expr: &SearchExpr, values: &mut Vec<String>) {
+    match expr {
+        SearchExpr::Word(value) | SearchExpr::Phrase(value) => values.push(value.clone()),
+        SearchExpr::BinaryOp(_, left, right) => {
+            collect_values(left, values);
+            collect_values(right, values);
         }
+        SearchExpr::Group(exprs) => exprs.iter().for_each(|expr| collect_values(expr, values)),
+        _ => {}
+    }
+}
+
+/// Whether the expression depends on the state of items for the searching user
+fn uses_item_state(expr: &SearchExpr) -> bool {
+    match expr {
+        SearchExpr::IsRead | SearchExpr::IsUnread | SearchExpr::IsStarred => true,
+        SearchExpr::BinaryOp(_, left, right) => uses_item_state(left) || uses_item_state(right),
+        SearchExpr::Not(expr) | SearchExpr::Field(_, expr) => uses_item_state(expr),
+        SearchExpr::Group(exprs) => exprs.iter().any(uses_item_state),
+        SearchExpr::Word(_) | SearchExpr::Phrase(_) => false,
     }
 }
 
 impl Query {
-    /// TODO: Think about this to_sql behavior more, especially now that we have adopted
-    pub fn to_sql(&self) -> (String, Vec<String>) {
-        let mut params = Vec::new();
-        let conditions: Vec<String> = self
-            .exprs
+    /// Removes groups which don't change the meaning of the query, such as groups with a
+    /// single expression or groups nested in other groups
+    pub fn normalized(self) -> Query {
+        Query {
+            exprs: normalize_group(self.exprs),
+        }
+    }
+
+    /// Returns the values the query uses for the field, e.g. the ids of `feed:1 OR feed:2`
+    pub fn field_values(&self, field: SearchField) -> Vec<String> {
+        let mut values = vec![];
+        self.exprs
             .iter()
-            .map(|expr| {
-                let (sql, new_params) = expr_to_sql(expr, &mut params);
-                params = new_params;
-                sql
-            })
-            .collect();
+            .for_each(|expr| collect_field_values(expr, field, &mut values));
+        values
+    }
+
+    /// Whether the query filters by the user's read or starred state (e.g. `is:unread`), which
+    /// mustn't be revealed to anyone else
+    pub fn uses_item_state(&self) -> bool {
+        self.exprs.iter().any(uses_item_state)
+    }
+
+    /// Compiles the query to a Postgres predicate over `feed_item` for the user, returning
+    /// the SQL and the parameters referenced by its `$n` placeholders
+    pub fn to_sql(&self, user_id: i32) -> (String, Vec<String>) {
+        let mut params = SqlParams::new();
+        let sql = self.to_sql_with_params(user_id, &mut params);
+        (sql, params.into_inner())
+    }
+
+    /// Compiles the query to a Postgres predicate over `feed_item` for the user, adding its
+    /// parameters to the provided ones
+    pub fn to_sql_with_params(&self, user_id: i32, params: &mut SqlParams) -> String {
+        let mut compiler = SqlCompiler {
+            user_id,
+            user_param: None,
+            params,
+        };
+        let conditions: Vec<String> = self.exprs.iter().map(|expr| compiler.expr(expr)).collect();
+        if conditions.is_empty() {
+            // Queries built from filters rather than parsed may not have any expressions
+            return "TRUE".to_string();
+        }
 
-        (conditions.join(" AND "), params)
+        conditions.join(" AND ")
+    }
+
+    /// Returns a `tsquery` expression matching any of the words and phrases of the query, or
+    /// nothing if the query doesn't search for any text
+    pub fn tsquery_sql(&self, params: &mut SqlParams) -> Option<String> {
+        let mut terms = vec![];
+        self.exprs
+            .iter()
+            .for_each(|expr| collect_text_terms(expr, &mut terms));
+        if terms.is_empty() {
+            return None;
+        }
+
+        Some(format!(
+            "websearch_to_tsquery('english', {})",
+            params.push(terms.join(" or "))
+        ))
+    }
+
+    /// Returns a `ts_rank` expression scoring how relevant an item is to the words and
+    /// phrases of the query, or nothing if the query doesn't search for any text
+    pub fn rank_sql(&self, params: &mut SqlParams) -> Option<String> {
+        self.tsquery_sql(params)
+            .map(|tsquery| format!("ts_rank(feed_item.search_vector, {})", tsquery))
     }
 }
 
@@ -284,6 +975,10 @@ mod tests {
         let parsed = parse_is("is:unread").expect("This should not fail");
         assert_eq!(parsed.1, SearchExpr::IsUnread);
         assert_eq!(parsed.0, "");
+
+        let parsed = parse_is("is:starred").expect("This should not fail");
+        assert_eq!(parsed.1, SearchExpr::IsStarred);
+        assert_eq!(parsed.0, "");
     }
 
     #[test]
@@ -292,7 +987,7 @@ mod tests {
         assert_eq!(
             parsed.1,
             SearchExpr::Field(
-                "title".to_string(),
+                SearchField::Title,
                 Box::new(SearchExpr::Word("hello".to_string()))
             )
         );
@@ -301,7 +996,7 @@ mod tests {
         assert_eq!(
             parsed.1,
             SearchExpr::Field(
-                "title".to_string(),
+                SearchField::Title,
                 Box::new(SearchExpr::Group(vec![
                     SearchExpr::Word("hello".to_string()),
                     SearchExpr::Word("world".to_string())
@@ -359,7 +1054,7 @@ mod tests {
         assert_eq!(
             parsed.1,
             SearchExpr::Not(Box::new(SearchExpr::Field(
-                "title".to_string(),
+                SearchField::Title,
                 Box::new(SearchExpr::Word("hello".to_string()))
             )))
         );
@@ -401,7 +1096,7 @@ mod tests {
     fn test_parsing_query() {
         let parsed = parse_query("hello world").unwrap();
         assert_eq!(
-            parsed.1.exprs,
+            parsed.exprs,
             vec![
                 SearchExpr::Word("hello".to_string()),
                 SearchExpr::Word("world".to_string())
@@ -410,12 +1105,12 @@ mod tests {
 
         let parsed = parse_query("hello world feed:123").unwrap();
         assert_eq!(
-            parsed.1.exprs,
+            parsed.exprs,
             vec![
                 SearchExpr::Word("hello".to_string()),
                 SearchExpr::Word("world".to_string()),
                 SearchExpr::Field(
-                    "feed".to_string(),
+                    SearchField::Feed,
                     Box::new(SearchExpr::Word("123".to_string()))
                 )
             ]
@@ -423,12 +1118,12 @@ mod tests {
 
         let parsed = parse_query("hello world feed:123 is:read").unwrap();
         assert_eq!(
-            parsed.1.exprs,
+            parsed.exprs,
             vec![
                 SearchExpr::Word("hello".to_string()),
                 SearchExpr::Word("world".to_string()),
                 SearchExpr::Field(
-                    "feed".to_string(),
+                    SearchField::Feed,
                     Box::new(SearchExpr::Word("123".to_string()))
                 ),
                 SearchExpr::IsRead
@@ -437,12 +1132,12 @@ mod tests {
 
         let parsed = parse_query("hello world feed:123 is:read OR is:unread").unwrap();
         assert_eq!(
-            parsed.1.exprs,
+            parsed.exprs,
             vec![
                 SearchExpr::Word("hello".to_string()),
                 SearchExpr::Word("world".to_string()),
                 SearchExpr::Field(
-                    "feed".to_string(),
+                    SearchField::Feed,
                     Box::new(SearchExpr::Word("123".to_string()))
                 ),
                 SearchExpr::BinaryOp(
@@ -456,23 +1151,23 @@ mod tests {
         let parsed =
             parse_query("\"hello world\" feed:123 OR feed:2456 is:read NOT title:hello").unwrap();
         assert_eq!(
-            parsed.1.exprs,
+            parsed.exprs,
             vec![
                 SearchExpr::Phrase("hello world".to_string()),
                 SearchExpr::BinaryOp(
                     BinaryOperator::Or,
                     Box::new(SearchExpr::Field(
-                        "feed".to_string(),
+                        SearchField::Feed,
                         Box::new(SearchExpr::Word("123".to_string()))
                     )),
                     Box::new(SearchExpr::Field(
-                        "feed".to_string(),
+                        SearchField::Feed,
                         Box::new(SearchExpr::Word("2456".to_string()))
                     ))
                 ),
                 SearchExpr::IsRead,
                 SearchExpr::Not(Box::new(SearchExpr::Field(
-                    "title".to_string(),
+                    SearchField::Title,
                     Box::new(SearchExpr::Word("hello".to_string()))
                 )))
             ]
@@ -482,27 +1177,250 @@ mod tests {
     #[test]
     fn to_sql_query() {
         let query = "\"hello world\" NOT title:hello".to_string();
-        let (_, parsed) = parse_query(&query).unwrap();
-        let (sql_query, values) = parsed.to_sql();
-        assert_eq!(sql_query, "content = ? AND NOT (title = ?)");
+        let parsed = parse_query(&query).unwrap();
+        let (sql_query, values) = parsed.to_sql(1);
+        assert_eq!(
+            sql_query,
+            "feed_item.search_vector @@ phraseto_tsquery('english', $1) \
+            AND NOT (to_tsvector('english', feed_item.title) @@ websearch_to_tsquery('english', $2))"
+        );
         assert_eq!(values, vec!["hello world", "hello"]);
 
         let query = "hello world feed:123 OR feed:2456 NOT title:hello".to_string();
-        let (_, parsed) = parse_query(&query).unwrap();
-        let (sql_query, values) = parsed.to_sql();
+        let parsed = parse_query(&query).unwrap();
+        let (sql_query, values) = parsed.to_sql(1);
         assert_eq!(
             sql_query,
-            "content ILIKE ? AND content ILIKE ? AND (feed = ? OR feed = ?) AND NOT (title = ?)"
+            "feed_item.search_vector @@ websearch_to_tsquery('english', $1) \
+            AND feed_item.search_vector @@ websearch_to_tsquery('english', $2) \
+            AND (feed_item.feed_id = $3::integer OR feed_item.feed_id = $4::integer) \
+            AND NOT (to_tsvector('english', feed_item.title) @@ websearch_to_tsquery('english', $5))"
         );
-        assert_eq!(values, vec!["%hello%", "%world%", "123", "2456", "hello"]);
+        assert_eq!(values, vec!["hello", "world", "123", "2456", "hello"]);
 
-        let query = "title:(hello world) type:(video OR podcasts)".to_string();
-        let (_, parsed) = parse_query(&query).unwrap();
-        let (sql_query, values) = parsed.to_sql();
+        let query = "title:(hello world) category:(video OR podcasts)".to_string();
+        let parsed = parse_query(&query).unwrap();
+        let (sql_query, values) = parsed.to_sql(1);
         assert_eq!(
             sql_query,
-            "(title = ? AND title = ?) AND (type = ? OR type = ?)"
+            "(to_tsvector('english', feed_item.title) @@ websearch_to_tsquery('english', $1) \
+            AND to_tsvector('english', feed_item.title) @@ websearch_to_tsquery('english', $2)) \
+            AND (EXISTS (SELECT 1 FROM unnest(feed_item.categories) AS category \
+            WHERE lower(category) = lower($3)) \
+            OR EXISTS (SELECT 1 FROM unnest(feed_item.categories) AS category \
+            WHERE lower(category) = lower($4)))"
         );
         assert_eq!(values, vec!["hello", "world", "video", "podcasts"]);
+
+        // OR binds tighter than the implicit AND between the words of a group
+        let parsed = parse_query("title:(rust OR go compiler)").unwrap();
+        let (sql_query, values) = parsed.to_sql(1);
+        assert_eq!(
+            sql_query,
+            "((to_tsvector('english', feed_item.title) @@ websearch_to_tsquery('english', $1) \
+            OR to_tsvector('english', feed_item.title) @@ websearch_to_tsquery('english', $2)) \
+            AND to_tsvector('english', feed_item.title) @@ websearch_to_tsquery('english', $3))"
+        );
+        assert_eq!(values, vec!["rust", "go", "compiler"]);
+    }
+
+    #[test]
+    fn to_sql_query_fields() {
+        let parsed =
+            parse_query("domain:Example.com lang:EN has:enclosure after:2025-01-31").unwrap();
+        let (sql_query, values) = parsed.to_sql(1);
+        assert_eq!(
+            sql_query,
+            "EXISTS (SELECT 1 FROM feed WHERE feed.id = feed_item.feed_id \
+            AND (lower(feed.domain) = $1 \
+            OR right(lower(feed.domain), length($1) + 1) = '.' || $1)) \
+            AND EXISTS (SELECT 1 FROM feed WHERE feed.id = feed_item.feed_id \
+            AND feed.language = $2) \
+            AND feed_item.enclosure IS NOT NULL \
+            AND COALESCE(feed_item.published_at, feed_item.created_at) >= $3::timestamptz"
+        );
+        assert_eq!(
+            values,
+            vec!["example.com", "en", "2025-01-31T00:00:00+00:00"]
+        );
+
+        let parsed = parse_query("tag:News list:7").unwrap();
+        let (sql_query, values) = parsed.to_sql(42);
+        assert_eq!(
+            sql_query,
+            "feed_item.feed_id IN (SELECT (child).id \
+            FROM user_tag, unnest(user_tag.children) AS child \
+            WHERE user_tag.owner = $1::integer AND (child).type = 'feed' \
+            AND (user_tag.id::text = $2 OR lower(user_tag.title) = lower($2))) \
+            AND feed_item.id IN (SELECT user_list_item.item FROM user_list_item \
+            JOIN user_list ON user_list.id = user_list_item.list \
+            WHERE user_list.owner = $1::integer \
+            AND (user_list.id::text = $3 OR lower(user_list.title) = lower($3)))"
+        );
+        assert_eq!(values, vec!["42", "News", "7"]);
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_invalid_values() {
+        assert!(parse_query("type:video").is_err());
+        assert!(parse_query("feed:abc").is_err());
+        assert!(parse_query("lang:english").is_err());
+        assert!(parse_query("has:image").is_err());
+        assert!(parse_query("before:yesterday").is_err());
+
+        assert!(parse_query("domain:blog.example.com before:2025-01-31T12:00:00Z").is_ok());
+        assert!(parse_query("author:\"Jane Doe\" feed:(1 OR 2)").is_ok());
+    }
+
+    #[test]
+    fn query_errors() {
+        let error = parse_query("rust titel:compilers").unwrap_err();
+        assert_eq!(error.offset, 5);
+        assert_eq!(
+            error.message,
+            "Unknown field `titel`, did you mean `title`?"
+        );
+        assert_eq!(error.suggestions, vec!["title"]);
+        assert!(error.expected.contains(&"author".to_string()));
+
+        let error = parse_query("hello (world OR rust").unwrap_err();
+        assert_eq!(error.offset, 6);
+        assert_eq!(error.expected, vec![")"]);
+
+        let error = parse_query("\"hello world").unwrap_err();
+        assert_eq!(error.offset, 0);
+        assert_eq!(error.message, "Phrase is never closed");
+
+        let error = parse_query("rust lang:english").unwrap_err();
+        assert_eq!(error.offset, 10);
+        assert_eq!(
+            error.message,
+            "lang must be a two letter language code (e.g. en), got \"english\""
+        );
+
+        let error = parse_query("is:unred").unwrap_err();
+        assert_eq!(error.offset, 3);
+        assert_eq!(error.expected, vec!["read", "unread", "starred"]);
+        assert_eq!(error.suggestions, vec!["unread"]);
+
+        let error = parse_query("hello \"world\"rust").unwrap_err();
+        assert_eq!(error.offset, 13);
+        assert_eq!(error.message, "Expected a space between expressions");
+
+        let error = parse_query("(hello feed:1)").unwrap_err();
+        assert_eq!(error.offset, 0);
+        assert_eq!(error.expected, GROUP_TOKENS);
+
+        let error = parse_query("NOT feed:").unwrap_err();
+        assert_eq!(error.offset, 9);
+        assert_eq!(error.expected, FIELD_VALUE_TOKENS);
+
+        assert_eq!(parse_query("  ").unwrap_err().message, "Query is empty");
+        assert_eq!(parse_query("hello)").unwrap_err().offset, 5);
+    }
+
+    #[test]
+    fn normalizes_queries() {
+        let query = parse_query("((rust)) (web (async)) title:((hello))")
+            .unwrap()
+            .normalized();
+        assert_eq!(
+            query.exprs,
+            vec![
+                SearchExpr::Word("rust".to_string()),
+                SearchExpr::Word("web".to_string()),
+                SearchExpr::Word("async".to_string()),
+                SearchExpr::Field(
+                    SearchField::Title,
+                    Box::new(SearchExpr::Word("hello".to_string()))
+                ),
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&query.exprs[3]).unwrap(),
+            serde_json::json!({
+                "type": "field",
+                "value": ["title", { "type": "word", "value": "hello" }]
+            })
+        );
+
+        let query = parse_query("feed:1 OR feed:2 NOT tag:(News OR 3)").unwrap();
+        assert_eq!(query.field_values(SearchField::Feed), vec!["1", "2"]);
+        assert_eq!(query.field_values(SearchField::Tag), vec!["News", "3"]);
+    }
+
+    #[test]
+    fn to_sql_query_after_existing_params() {
+        let parsed = parse_query("hello").unwrap();
+        let mut params = SqlParams::new();
+        params.push(1);
+
+        let sql_query = parsed.to_sql_with_params(1, &mut params);
+        assert_eq!(
+            sql_query,
+            "feed_item.search_vector @@ websearch_to_tsquery('english', $2)"
+        );
+        assert_eq!(params.into_inner(), vec!["1", "hello"]);
+    }
+
+    #[test]
+    fn to_sql_query_read_state() {
+        let parsed = parse_query("hello is:unread OR is:read").unwrap();
+        let (sql_query, values) = parsed.to_sql(42);
+        assert_eq!(
+            sql_query,
+            "feed_item.search_vector @@ websearch_to_tsquery('english', $1) \
+            AND (NOT EXISTS (SELECT 1 FROM user_item_state \
+            WHERE user_item_state.item = feed_item.id AND user_item_state.owner = $2::integer \
+            AND user_item_state.read) \
+            OR EXISTS (SELECT 1 FROM user_item_state \
+            WHERE user_item_state.item = feed_item.id AND user_item_state.owner = $2::integer \
+            AND user_item_state.read))"
+        );
+        assert_eq!(values, vec!["hello", "42"]);
+    }
+
+    #[test]
+    fn detects_item_state() {
+        assert!(parse_query("rust is:unread").unwrap().uses_item_state());
+        assert!(parse_query("(rust OR is:starred)")
+            .unwrap()
+            .uses_item_state());
+        assert!(!parse_query("rust title:read tag:News")
+            .unwrap()
+            .uses_item_state());
+    }
+
+    #[test]
+    fn to_sql_query_starred() {
+        let parsed = parse_query("is:starred OR is:unread").unwrap();
+        let (sql_query, values) = parsed.to_sql(42);
+        assert_eq!(
+            sql_query,
+            "(EXISTS (SELECT 1 FROM user_item_state \
+            WHERE user_item_state.item = feed_item.id AND user_item_state.owner = $1::integer \
+            AND user_item_state.starred) \
+            OR NOT EXISTS (SELECT 1 FROM user_item_state \
+            WHERE user_item_state.item = feed_item.id AND user_item_state.owner = $1::integer \
+            AND user_item_state.read))"
+        );
+        assert_eq!(values, vec!["42"]);
+    }
+
+    #[test]
+    fn rank_sql_query() {
+        let parsed = parse_query("(hello OR \"big world\") NOT bye title:test").unwrap();
+        let mut params = SqlParams::new();
+        let rank = parsed.rank_sql(&mut params);
+        assert_eq!(
+            rank,
+            Some(
+                "ts_rank(feed_item.search_vector, websearch_to_tsquery('english', $1))".to_string()
+            )
+        );
+        assert_eq!(params.into_inner(), vec!["hello or \"big world\""]);
+
+        let parsed = parse_query("title:test").unwrap();
+        assert_eq!(parsed.rank_sql(&mut SqlParams::new()), None);
     }
 }
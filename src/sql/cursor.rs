@@ -0,0 +1,419 @@
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::query::SqlParams;
+
+/// Most rows which can be requested in a single page
+const MAX_PAGE_SIZE: i64 = 100;
+
+/// Position of an item in an ordering, made of the values of the ordering's keys and the id of
+/// the item. Clients receive it as an opaque string so that its contents can change freely
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Cursor {
+    pub keys: Vec<String>,
+    pub id: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncodedCursor {
+    k: Vec<String>,
+    i: i64,
+}
+
+impl From<Cursor> for String {
+    fn from(cursor: Cursor) -> Self {
+        let encoded = EncodedCursor {
+            k: cursor.keys,
+            i: cursor.id,
+        };
+        serde_json::to_vec(&encoded)
+            .unwrap()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+impl TryFrom<String> for Cursor {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || "Invalid cursor".to_string();
+        let bytes = value
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| {
+                std::str::from_utf8(pair)
+                    .ok()
+                    .filter(|pair| pair.len() == 2)
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+        let decoded: EncodedCursor = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+
+        Ok(Cursor {
+            keys: decoded.k,
+            id: decoded.i,
+        })
+    }
+}
+
+/// Type of the value of a sort key, which cursors are checked against and cast to in SQL
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortKeyType {
+    Integer,
+    Real,
+    Text,
+    Timestamp,
+}
+
+impl SortKeyType {
+    fn cast(&self) -> &'static str {
+        match self {
+            SortKeyType::Integer => "bigint",
+            SortKeyType::Real => "real",
+            SortKeyType::Text => "text",
+            SortKeyType::Timestamp => "timestamptz",
+        }
+    }
+
+    fn is_valid(&self, value: &str) -> bool {
+        match self {
+            SortKeyType::Integer => value.parse::<i64>().is_ok(),
+            SortKeyType::Real => value.parse::<f32>().is_ok(),
+            SortKeyType::Text => true,
+            // Postgres' text output of timestamps, or RFC 3339 for cursors built from rows
+            SortKeyType::Timestamp => {
+                DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%#z").is_ok()
+                    || DateTime::parse_from_rfc3339(value).is_ok()
+            }
+        }
+    }
+}
+
+/// Expression which rows are ordered by
+#[derive(Clone, Debug, PartialEq)]
+pub struct SortKey {
+    pub sql: String,
+    pub key_type: SortKeyType,
+}
+
+/// Ordering of rows by some keys and then their id, all in the same direction so that the
+/// position of a row can be compared against a cursor with a single row comparison
+#[derive(Clone, Debug, PartialEq)]
+pub struct RowOrder {
+    pub keys: Vec<SortKey>,
+    /// Column of the row's id, which breaks ties between rows with the same keys
+    pub id: &'static str,
+    pub descending: bool,
+}
+
+impl RowOrder {
+    pub fn new(id: &'static str, descending: bool) -> Self {
+        Self {
+            keys: vec![],
+            id,
+            descending,
+        }
+    }
+
+    pub fn key(mut self, sql: impl Into<String>, key_type: SortKeyType) -> Self {
+        self.keys.push(SortKey {
+            sql: sql.into(),
+            key_type,
+        });
+        self
+    }
+
+    /// Orders rows by the expression first, in the same direction as the other keys
+    pub fn key_first(mut self, sql: impl Into<String>, key_type: SortKeyType) -> Self {
+        self.keys.insert(
+            0,
+            SortKey {
+                sql: sql.into(),
+                key_type,
+            },
+        );
+        self
+    }
+
+    /// Checks that the cursor was created for this ordering
+    pub fn accepts(&self, cursor: &Cursor) -> bool {
+        cursor.keys.len() == self.keys.len()
+            && self
+                .keys
+                .iter()
+                .zip(&cursor.keys)
+                .all(|(key, value)| key.key_type.is_valid(value))
+    }
+
+    /// Returns the `ORDER BY` expression, reversed when paging backwards from a cursor
+    pub fn order_by_sql(&self, reverse: bool) -> String {
+        let direction = if self.descending != reverse {
+            "DESC"
+        } else {
+            "ASC"
+        };
+        self.keys
+            .iter()
+            .map(|key| key.sql.as_str())
+            .chain([self.id])
+            .map(|sql| format!("{} {}", sql, direction))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Returns an expression with the text of the keys of a row, to build its cursor from
+    pub fn cursor_keys_sql(&self) -> String {
+        let keys: Vec<String> = self
+            .keys
+            .iter()
+            .map(|key| format!("({})::text", key.sql))
+            .collect();
+        format!("ARRAY[{}]::text[]", keys.join(", "))
+    }
+
+    /// Returns a predicate matching the rows after the cursor in the ordering, or before it
+    /// when `reverse` is set. The cursor must have been checked with [RowOrder::accepts]
+    pub fn cursor_sql(&self, cursor: &Cursor, reverse: bool, params: &mut SqlParams) -> String {
+        let columns: Vec<&str> = self
+            .keys
+            .iter()
+            .map(|key| key.sql.as_str())
+            .chain([self.id])
+            .collect();
+        let mut values: Vec<String> = self
+            .keys
+            .iter()
+            .zip(&cursor.keys)
+            .map(|(key, value)| format!("{}::{}", params.push(value), key.key_type.cast()))
+            .collect();
+        values.push(format!("{}::bigint", params.push(cursor.id)));
+        let operator = if self.descending != reverse { "<" } else { ">" };
+
+        format!(
+            "({}) {} ({})",
+            columns.join(", "),
+            operator,
+            values.join(", ")
+        )
+    }
+}
+
+/// Cursor pagination parameters. Pass the `nextCursor` of a page as `after` to get the rows
+/// after it, or the `prevCursor` as `before` to get the rows before it (e.g. newer items at
+/// the top of a timeline). Rows inserted while paging are never skipped or repeated
+#[derive(Deserialize, Debug, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CursorPagination {
+    /// Only return rows after the cursor
+    #[param(value_type = Option<String>)]
+    pub after: Option<Cursor>,
+    /// Only return rows before the cursor
+    #[param(value_type = Option<String>)]
+    pub before: Option<Cursor>,
+    /// Maximum number of rows to return, between 1 and 100
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    20
+}
+
+/// Pagination parameters which can't be used with an ordering
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct PaginationError(&'static str);
+
+impl CursorPagination {
+    /// Checks the limit and that the cursor was created for the ordering
+    pub fn validate_for(&self, order: &RowOrder) -> Result<(), PaginationError> {
+        if !(1..=MAX_PAGE_SIZE).contains(&self.limit) {
+            return Err(PaginationError("Limit must be between 1 and 100"));
+        }
+        match (&self.after, &self.before) {
+            (Some(_), Some(_)) => Err(PaginationError("Only one of after and before can be used")),
+            (Some(cursor), None) | (None, Some(cursor)) if !order.accepts(cursor) => Err(
+                PaginationError("Cursor doesn't match the order of the rows"),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether rows are fetched backwards from the `before` cursor
+    fn is_reversed(&self) -> bool {
+        self.before.is_some()
+    }
+}
+
+/// A page of rows, with the cursors to continue paging from either end of it
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor to pass as `after` to get the rows after this page, if there are any
+    #[schema(value_type = Option<String>)]
+    pub next_cursor: Option<Cursor>,
+    /// Cursor to pass as `before` to get the rows before this page, if there may be any
+    #[schema(value_type = Option<String>)]
+    pub prev_cursor: Option<Cursor>,
+}
+
+impl<T> Page<T> {
+    /// Builds a page from rows fetched in the requested direction, with one extra row to tell
+    /// whether there are more rows past the page
+    fn from_rows(mut rows: Vec<(T, Cursor)>, pagination: &CursorPagination) -> Self {
+        let has_more = rows.len() as i64 > pagination.limit;
+        rows.truncate(pagination.limit as usize);
+
+        let (has_next, has_prev) = if pagination.is_reversed() {
+            rows.reverse();
+            (true, has_more)
+        } else {
+            (has_more, pagination.after.is_some())
+        };
+        let next_cursor = rows
+            .last()
+            .filter(|_| has_next)
+            .map(|(_, cursor)| cursor.clone());
+        let prev_cursor = rows
+            .first()
+            .filter(|_| has_prev)
+            .map(|(_, cursor)| cursor.clone());
+
+        Self {
+            items: rows.into_iter().map(|(item, _)| item).collect(),
+            next_cursor,
+            prev_cursor,
+        }
+    }
+}
+
+/// Row along with the keys of its position in the ordering it was fetched with
+struct CursorRow<T> {
+    row: T,
+    cursor_keys: Vec<String>,
+    cursor_id: i64,
+}
+
+impl<'r, T: sqlx::FromRow<'r, PgRow>> sqlx::FromRow<'r, PgRow> for CursorRow<T> {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            row: T::from_row(row)?,
+            cursor_keys: row.try_get("cursor_keys")?,
+            cursor_id: row.try_get("cursor_id")?,
+        })
+    }
+}
+
+/// Fetches a page of `SELECT {columns} FROM {from} WHERE {condition}` in the order, where the
+/// parameters of the query have been added to `params`. The pagination must have been checked
+/// with [CursorPagination::validate_for]
+pub async fn fetch_page<T>(
+    pool: &sqlx::PgPool,
+    columns: &str,
+    from: &str,
+    condition: &str,
+    order: &RowOrder,
+    pagination: &CursorPagination,
+    mut params: SqlParams,
+) -> sqlx::Result<Page<T>>
+where
+    T: for<'r> sqlx::FromRow<'r, PgRow> + Send + Unpin,
+{
+    let reverse = pagination.is_reversed();
+    let mut conditions = vec![condition.to_string()];
+    if let Some(cursor) = pagination.after.as_ref().or(pagination.before.as_ref()) {
+        conditions.push(order.cursor_sql(cursor, reverse, &mut params));
+    }
+    let limit = params.push(pagination.limit + 1);
+
+    let sql = format!(
+        "SELECT {}, {} AS cursor_keys, {}::bigint AS cursor_id FROM {} WHERE {} \
+        ORDER BY {} LIMIT {}::bigint",
+        columns,
+        order.cursor_keys_sql(),
+        order.id,
+        from,
+        conditions.join(" AND "),
+        order.order_by_sql(reverse),
+        limit
+    );
+    let mut query = sqlx::query_as::<_, CursorRow<T>>(&sql);
+    for param in params.into_inner() {
+        query = query.bind(param);
+    }
+
+    let rows = query.fetch_all(pool).await?;
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            let cursor = Cursor {
+                keys: row.cursor_keys,
+                id: row.cursor_id,
+            };
+            (row.row, cursor)
+        })
+        .collect();
+    Ok(Page::from_rows(rows, pagination))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_cursors() {
+        let cursor = Cursor {
+            keys: vec!["2025-01-31 12:00:00.5+00".to_string()],
+            id: 42,
+        };
+        let encoded = String::from(cursor.clone());
+        assert!(encoded.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(Cursor::try_from(encoded), Ok(cursor));
+
+        assert!(Cursor::try_from("not a cursor".to_string()).is_err());
+        assert!(Cursor::try_from("7b7d".to_string()).is_err());
+        assert!(Cursor::try_from("7b7".to_string()).is_err());
+    }
+
+    #[test]
+    fn builds_keyset_predicates() {
+        let order = RowOrder::new("feed_item.id", true)
+            .key("feed_item.updated_at", SortKeyType::Timestamp)
+            .key_first("rank", SortKeyType::Real);
+        let cursor = Cursor {
+            keys: vec!["0.5".to_string(), "2025-01-31T12:00:00Z".to_string()],
+            id: 7,
+        };
+        assert!(order.accepts(&cursor));
+        assert!(!order.accepts(&Cursor {
+            keys: vec!["yesterday".to_string(), "0.5".to_string()],
+            id: 7
+        }));
+
+        let mut params = SqlParams::new();
+        assert_eq!(
+            order.cursor_sql(&cursor, false, &mut params),
+            "(rank, feed_item.updated_at, feed_item.id) < ($1::real, $2::timestamptz, $3::bigint)"
+        );
+        assert_eq!(
+            order.order_by_sql(false),
+            "rank DESC, feed_item.updated_at DESC, feed_item.id DESC"
+        );
+        assert_eq!(
+            order.order_by_sql(true),
+            "rank ASC, feed_item.updated_at ASC, feed_item.id ASC"
+        );
+        assert_eq!(
+            order.cursor_sql(&cursor, true, &mut SqlParams::new()),
+            "(rank, feed_item.updated_at, feed_item.id) > ($1::real, $2::timestamptz, $3::bigint)"
+        );
+    }
+}
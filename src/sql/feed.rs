@@ -6,6 +6,7 @@ use mockito::ServerGuard;
 
 use crate::feed::{
     daemon::FeedUpdate,
+    icon::FetchedIcon,
     parser::{feed::ParsedFeed, feed_item::ParsedFeedItem},
 };
 
@@ -65,6 +66,7 @@ pub struct Feed {
     #[ormx(custom_type)]
     pub format: FeedFormat,
     /// URL of the feed
+    #[ormx(get_optional = get_by_link)]
     pub link: String,
     /// Domain of the feed (extracted from the URL)
     pub domain: Option<String>,
@@ -154,6 +156,7 @@ impl Feed {
             .clone()
             .unwrap_or(self.description.clone());
         self.icon = update.icon.clone().or(self.icon.clone());
+        self.language = update.language.clone().or(self.language.clone());
 
         self.skip_hours = update.skip_hours.clone().unwrap_or(self.skip_hours.clone());
         self.skip_days_of_week = update
@@ -233,6 +236,8 @@ pub struct FeedItem {
     pub link: Option<String>,
     /// Summary or description of the item
     pub description: Option<String>,
+    /// Name of the item's author
+    pub author: Option<String>,
     /// Attached media file (if any)
     #[ormx(custom_type, by_ref)]
     pub enclosure: Option<FeedItemEnclosure>,
@@ -265,6 +270,7 @@ impl FeedItem {
         self.title = parsed.title.clone();
         self.link = parsed.link.clone().or(self.link.clone());
         self.description = parsed.description.clone().or(self.description.clone());
+        self.author = parsed.author.clone().or(self.author.clone());
         self.enclosure = parsed.enclosure.clone().or(self.enclosure.clone());
         self.categories = parsed.categories.clone();
         self.comments_link = parsed.comments_link.clone().or(self.comments_link.clone());
@@ -287,6 +293,7 @@ impl InsertFeedItem {
             title: item.title.clone(),
             link: item.link.clone(),
             description: item.description.clone(),
+            author: item.author.clone(),
             enclosure: item.enclosure.clone(),
             categories: item.categories.clone(),
             comments_link: item.comments_link.clone(),
@@ -311,7 +318,7 @@ impl From<ParsedFeed> for InsertFeed {
             title: parsed_feed.title,
             description: parsed_feed.description,
             icon: parsed_feed.icon,
-            language: None,
+            language: parsed_feed.language,
 
             skip_hours: parsed_feed.skip_hours,
             skip_days_of_week: parsed_feed.skip_days_of_week,
@@ -343,3 +350,87 @@ pub struct FeedItemParsed {
     #[ormx(default)]
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
+
+/// Icon of a feed's website, downloaded by the feed daemon
+///
+/// A row without data means no icon could be found when it was last fetched
+#[derive(Clone, Debug, PartialEq, sqlx::FromRow, ormx::Table)]
+#[ormx(table = "feed_icon", id = id, deletable)]
+pub struct FeedIcon {
+    #[ormx(default)]
+    pub id: i32,
+    #[ormx(get_optional = get_by_feed)]
+    pub feed: i32,
+    /// URL the icon was downloaded from
+    pub link: Option<String>,
+    /// MIME type of the icon
+    pub content_type: Option<String>,
+    /// Raw bytes of the icon
+    pub data: Option<Vec<u8>>,
+    /// Hex encoded SHA-256 hash of the data, used as the ETag
+    pub hash: Option<String>,
+    /// When the icon was last fetched
+    pub fetched_at: chrono::DateTime<chrono::Utc>,
+    #[ormx(default)]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[ormx(default)]
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl FeedIcon {
+    /// Stores the result of fetching the icon for the feed, replacing the previous one
+    pub async fn upsert(
+        pool: &sqlx::PgPool,
+        feed: i32,
+        icon: Option<&FetchedIcon>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO feed_icon (feed, link, content_type, data, hash, fetched_at)
+            VALUES ($1, $2, $3, $4, encode(sha256($4), 'hex'), NOW())
+            ON CONFLICT (feed) DO UPDATE SET
+                link = EXCLUDED.link,
+                content_type = EXCLUDED.content_type,
+                data = EXCLUDED.data,
+                hash = EXCLUDED.hash,
+                fetched_at = EXCLUDED.fetched_at,
+                updated_at = NOW()
+            "#,
+            feed,
+            icon.map(|icon| icon.link.as_str()),
+            icon.map(|icon| icon.content_type.as_str()),
+            icon.map(|icon| icon.data.as_slice()),
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl Feed {
+    /// Returns the feeds which have never had their icon fetched or were last fetched more
+    /// than a week ago
+    pub async fn get_missing_icons(
+        pool: &sqlx::PgPool,
+        limit: i64,
+    ) -> Result<Vec<(i32, String)>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+            SELECT feed.id, feed.link
+            FROM feed
+            LEFT JOIN feed_icon ON feed_icon.feed = feed.id
+            WHERE feed.status = 'active'
+                AND (feed_icon.id IS NULL OR feed_icon.fetched_at < NOW() - INTERVAL '7 days')
+            ORDER BY feed_icon.fetched_at NULLS FIRST, feed.id
+            LIMIT $1
+            "#,
+            limit
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.id, row.link))
+        .collect())
+    }
+}
@@ -1,4 +1,5 @@
-use super::Icon;
+use super::{Icon, SortOrder, Visibility};
+use rand::{distr::Alphanumeric, Rng};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -13,15 +14,78 @@ pub struct UserIndex {
     pub owner: i32,
 
     pub query: String,
-    pub sort: String,
+    #[ormx(custom_type)]
+    pub sort: SortOrder,
 
     pub title: String,
     pub description: Option<String>,
     #[ormx(custom_type, by_ref)]
     pub icon: Icon,
 
+    /// Who can see the index
+    #[ormx(custom_type)]
+    pub visibility: Visibility,
+    /// Identifier of the index in its public URL
+    #[ormx(get_optional = get_by_slug)]
+    pub slug: String,
+
     #[ormx(default)]
     pub created_at: chrono::DateTime<chrono::Utc>,
     #[ormx(default, set)]
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
+
+/// Length of the secret tokens of index feeds
+const FEED_TOKEN_LENGTH: usize = 32;
+
+/// Secret which allows reading an index as an RSS, Atom or JSON feed without authentication
+#[derive(Clone, Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct UserIndexFeed {
+    pub index: i32,
+    pub token: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl UserIndexFeed {
+    /// Creates a new token for the index, replacing and so revoking the previous one
+    pub async fn rotate(pool: &sqlx::PgPool, index: i32) -> sqlx::Result<Self> {
+        let token: String = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(FEED_TOKEN_LENGTH)
+            .map(char::from)
+            .collect();
+
+        sqlx::query_as!(
+            UserIndexFeed,
+            r#"
+            INSERT INTO user_index_feed (index, token) VALUES ($1, $2)
+            ON CONFLICT (index) DO UPDATE SET token = EXCLUDED.token, created_at = NOW()
+            RETURNING index, token, created_at
+            "#,
+            index,
+            token
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Revokes the token of the index, returning whether it had one
+    pub async fn revoke(pool: &sqlx::PgPool, index: i32) -> sqlx::Result<bool> {
+        let result = sqlx::query!("DELETE FROM user_index_feed WHERE index = $1", index)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns whether the token allows reading the index as a feed
+    pub async fn is_valid(pool: &sqlx::PgPool, index: i32, token: &str) -> sqlx::Result<bool> {
+        let valid = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM user_index_feed WHERE index = $1 AND token = $2) AS "valid!""#,
+            index,
+            token
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(valid)
+    }
+}
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::query::SqlParams;
+
+/// Represents the state of a feed item for a single user, such as whether it has been read.
+/// Items without a state are unread and not starred
+#[derive(Clone, Debug, Serialize, Deserialize, sqlx::FromRow, ormx::Table, ToSchema)]
+#[ormx(table = "user_item_state", id = id, insertable, deletable)]
+pub struct UserItemState {
+    #[ormx(default)]
+    pub id: i64,
+    pub owner: i32,
+    /// The id of the feed item this state is for
+    pub item: i64,
+    /// Whether the user has read the item
+    pub read: bool,
+    /// When the user read the item
+    pub read_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Whether the user has starred the item
+    pub starred: bool,
+    /// When the user starred the item
+    pub starred_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[ormx(default)]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[ormx(default, set)]
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl UserItemState {
+    /// Marks the items as read or unread for the user, returning the number of items updated.
+    /// Ids of items which don't exist are ignored
+    pub async fn set_read(
+        pool: &sqlx::PgPool,
+        owner: i32,
+        items: &[i64],
+        read: bool,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO user_item_state (owner, item, read, read_at)
+            SELECT $1, feed_item.id, $3, CASE WHEN $3 THEN NOW() END
+            FROM (SELECT DISTINCT unnest($2::bigint[]) AS id) AS items
+            JOIN feed_item ON feed_item.id = items.id
+            ON CONFLICT (owner, item) DO UPDATE SET
+                read = EXCLUDED.read,
+                -- Keep the original time when marking an already read item as read
+                read_at = CASE
+                    WHEN user_item_state.read AND EXCLUDED.read THEN user_item_state.read_at
+                    ELSE EXCLUDED.read_at
+                END,
+                updated_at = NOW()
+            "#,
+            owner,
+            items,
+            read
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Stars or unstars the items for the user, returning the number of items updated. Items
+    /// which were already starred keep the time they were first starred at. Ids of items which
+    /// don't exist are ignored
+    pub async fn set_starred(
+        pool: &sqlx::PgPool,
+        owner: i32,
+        items: &[i64],
+        starred: bool,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO user_item_state (owner, item, starred, starred_at)
+            SELECT $1, feed_item.id, $3, CASE WHEN $3 THEN NOW() END
+            FROM (SELECT DISTINCT unnest($2::bigint[]) AS id) AS items
+            JOIN feed_item ON feed_item.id = items.id
+            ON CONFLICT (owner, item) DO UPDATE SET
+                starred = EXCLUDED.starred,
+                starred_at = CASE
+                    WHEN user_item_state.starred AND EXCLUDED.starred THEN user_item_state.starred_at
+                    ELSE EXCLUDED.starred_at
+                END,
+                updated_at = NOW()
+            "#,
+            owner,
+            items,
+            starred
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Marks the items of the feeds published before the time as read for the user, returning
+    /// the number of items which were unread
+    pub async fn mark_feeds_read(
+        pool: &sqlx::PgPool,
+        owner: i32,
+        feeds: &[i32],
+        before: chrono::DateTime<chrono::Utc>,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO user_item_state (owner, item, read, read_at)
+            SELECT $1, feed_item.id, true, NOW()
+            FROM feed_item
+            WHERE feed_item.feed_id = ANY($2)
+                AND COALESCE(feed_item.published_at, feed_item.created_at) < $3
+            ON CONFLICT (owner, item) DO UPDATE SET
+                read = true,
+                read_at = NOW(),
+                updated_at = NOW()
+            WHERE NOT user_item_state.read
+            "#,
+            owner,
+            feeds,
+            before
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Marks every item matching the condition, a predicate over `feed_item` whose parameters
+    /// have been added to `params`, as read for the user in a single statement. Returns the
+    /// number of items which were unread
+    pub async fn mark_read_matching(
+        pool: &sqlx::PgPool,
+        owner: i32,
+        condition: &str,
+        mut params: SqlParams,
+    ) -> sqlx::Result<u64> {
+        let sql = format!(
+            "INSERT INTO user_item_state (owner, item, read, read_at) \
+            SELECT {}::integer, feed_item.id, true, NOW() FROM feed_item WHERE {} \
+            ON CONFLICT (owner, item) DO UPDATE SET \
+            read = true, read_at = NOW(), updated_at = NOW() \
+            WHERE NOT user_item_state.read",
+            params.push(owner),
+            condition
+        );
+        let mut query = sqlx::query(&sql);
+        for param in params.into_inner() {
+            query = query.bind(param);
+        }
+
+        Ok(query.execute(pool).await?.rows_affected())
+    }
+}
@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::job::JobKind;
+
+/// Delay before the first retry of a failed job, doubled on every following attempt
+const RETRY_BASE_DELAY_SECONDS: i64 = 30;
+/// Longest delay between two attempts of a job
+const RETRY_MAX_DELAY_SECONDS: i64 = 60 * 60;
+/// Running jobs which haven't been updated for this long are assumed to belong to a worker
+/// which died, and are claimed again or failed if they were on their last attempt
+const STALE_JOB_MINUTES: i32 = 15;
+
+/// State of a job in the queue
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "job_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Waiting for a worker, either for the first time or to be retried
+    Pending,
+    Running,
+    Completed,
+    /// Failed on every attempt
+    Failed,
+}
+
+/// Long running work, such as importing subscriptions, which runs in the background of the
+/// daemon and can be polled for its progress
+#[derive(Clone, Debug, Serialize, Deserialize, sqlx::FromRow, ormx::Table, ToSchema)]
+#[ormx(table = "job", id = id, deletable)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    #[ormx(default)]
+    pub id: i64,
+    /// The user who queued the job, if any
+    pub owner: Option<i32>,
+    /// Name of the kind of job
+    pub kind: String,
+    /// Parameters of the job, which depend on its kind
+    #[serde(skip)]
+    pub payload: serde_json::Value,
+    #[ormx(custom_type)]
+    pub status: JobStatus,
+
+    /// Number of steps done, out of total when it is known
+    pub progress: i32,
+    pub total: Option<i32>,
+    /// Outcome of a completed job, which depends on its kind
+    #[schema(value_type = Option<Object>)]
+    pub result: Option<serde_json::Value>,
+    /// Error of the last failed attempt
+    pub error: Option<String>,
+
+    pub attempts: i32,
+    pub max_attempts: i32,
+    /// When the job will next be run, if it is pending
+    pub run_at: chrono::DateTime<chrono::Utc>,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Job {
+    /// Adds a job to the queue, to be run as soon as a worker is available
+    pub async fn enqueue(
+        pool: &sqlx::PgPool,
+        owner: Option<i32>,
+        kind: &JobKind,
+    ) -> sqlx::Result<Self> {
+        let (name, payload) = kind.to_parts();
+        sqlx::query_as!(
+            Job,
+            r#"
+            INSERT INTO job (owner, kind, payload) VALUES ($1, $2, $3)
+            RETURNING id, owner, kind, payload, status as "status:JobStatus", progress, total,
+                result, error, attempts, max_attempts, run_at, started_at, finished_at,
+                created_at, updated_at
+            "#,
+            owner,
+            name,
+            payload
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Marks the next job which is due as running and returns it. Jobs are locked with
+    /// SKIP LOCKED, so concurrent workers never claim the same job
+    pub async fn claim(pool: &sqlx::PgPool) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Job,
+            r#"
+            UPDATE job SET
+                status = 'running',
+                attempts = attempts + 1,
+                started_at = NOW(),
+                updated_at = NOW()
+            WHERE id = (
+                SELECT id FROM job
+                WHERE attempts < max_attempts AND (
+                    (status = 'pending' AND run_at <= NOW())
+                    OR (status = 'running' AND updated_at < NOW() - make_interval(mins => $1))
+                )
+                ORDER BY run_at, id
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, owner, kind, payload, status as "status:JobStatus", progress, total,
+                result, error, attempts, max_attempts, run_at, started_at, finished_at,
+                created_at, updated_at
+            "#,
+            STALE_JOB_MINUTES
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Fails the stale running jobs which have no attempts left, since they are never claimed
+    /// again. Returns how many jobs were failed
+    pub async fn fail_stale(pool: &sqlx::PgPool) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE job SET
+                status = 'failed',
+                error = 'The worker running the job stopped',
+                finished_at = NOW(),
+                updated_at = NOW()
+            WHERE status = 'running' AND attempts >= max_attempts
+                AND updated_at < NOW() - make_interval(mins => $1)
+            "#,
+            STALE_JOB_MINUTES
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Records how many steps of the job are done, which also keeps it from being seen as stale
+    pub async fn set_progress(
+        pool: &sqlx::PgPool,
+        id: i64,
+        progress: i32,
+        total: Option<i32>,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            "UPDATE job SET progress = $2, total = $3, updated_at = NOW() WHERE id = $1",
+            id,
+            progress,
+            total
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn complete(
+        pool: &sqlx::PgPool,
+        id: i64,
+        result: serde_json::Value,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE job SET
+                status = 'completed',
+                result = $2,
+                error = NULL,
+                finished_at = NOW(),
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+            result
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Records a failed attempt of the job, which is retried with exponential backoff until it
+    /// runs out of attempts, unless `retry` is false
+    pub async fn fail(&self, pool: &sqlx::PgPool, error: &str, retry: bool) -> sqlx::Result<()> {
+        let (status, run_at, finished_at) = match retry && self.attempts < self.max_attempts {
+            true => (
+                JobStatus::Pending,
+                chrono::Utc::now() + Self::retry_delay(self.attempts),
+                None,
+            ),
+            false => (JobStatus::Failed, self.run_at, Some(chrono::Utc::now())),
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE job SET
+                status = $2,
+                error = $3,
+                run_at = $4,
+                finished_at = $5,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+            self.id,
+            status as JobStatus,
+            error,
+            run_at,
+            finished_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Delay before retrying a job which failed on its nth attempt
+    pub fn retry_delay(attempts: i32) -> chrono::Duration {
+        let exponent = attempts.clamp(1, 20) as u32 - 1;
+        let seconds = RETRY_BASE_DELAY_SECONDS.saturating_mul(2i64.pow(exponent));
+        chrono::Duration::seconds(seconds.min(RETRY_MAX_DELAY_SECONDS))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn backs_off_exponentially() {
+        assert_eq!(Job::retry_delay(1), chrono::Duration::seconds(30));
+        assert_eq!(Job::retry_delay(2), chrono::Duration::seconds(60));
+        assert_eq!(Job::retry_delay(4), chrono::Duration::seconds(240));
+        assert_eq!(Job::retry_delay(12), chrono::Duration::hours(1));
+        assert_eq!(Job::retry_delay(100), chrono::Duration::hours(1));
+    }
+}
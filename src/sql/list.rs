@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-use super::Icon;
+use super::{Icon, Visibility};
 
 /// Represent a list created by a user
 /// Lists are a way for users to catalogue items from feeds
@@ -15,6 +15,12 @@ pub struct UserList {
     pub description: Option<String>,
     #[ormx(custom_type, by_ref)]
     pub icon: Option<Icon>,
+    /// Who can see the list
+    #[ormx(custom_type)]
+    pub visibility: Visibility,
+    /// Identifier of the list in its public URL
+    #[ormx(get_optional = get_by_slug)]
+    pub slug: String,
     #[ormx(default)]
     pub created_at: chrono::DateTime<chrono::Utc>,
     #[ormx(default)]
@@ -1,21 +1,33 @@
-use std::fmt::Display;
+use std::sync::LazyLock;
 
-use rand::Rng;
+use rand::{distr::Alphanumeric, Rng};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::query::{Query, SqlParams};
+
+mod cursor;
 mod feed;
 mod history;
 mod index;
+mod item_state;
+mod job;
 mod list;
+mod subscription;
 pub mod tags;
+mod timeline;
 mod user;
 
+pub use cursor::*;
 pub use feed::*;
 pub use history::*;
 pub use index::*;
+pub use item_state::*;
+pub use job::*;
 pub use list::*;
+pub use subscription::*;
 pub use tags::*;
+pub use timeline::*;
 pub use user::*;
 
 #[derive(Clone, Debug, Serialize, Deserialize, sqlx::FromRow, sqlx::Type, ToSchema)]
@@ -39,35 +51,96 @@ impl Icon {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, sqlx::Type, ToSchema)]
-#[sqlx(type_name = "sort_order")]
-pub enum SortOrder {
-    RecentlyUpdated,
-    AsIs,
+/// Who can see an index or a list
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "visibility", rename_all = "lowercase")]
+pub enum Visibility {
+    /// Only the owner
+    #[default]
+    Private,
+    /// Anyone with the link, without being listed publicly
+    Unlisted,
+    /// Anyone, and listed publicly
+    Public,
 }
 
-impl From<&str> for SortOrder {
-    fn from(s: &str) -> Self {
-        match s {
-            "recently_updated" => SortOrder::RecentlyUpdated,
-            "as_is" => SortOrder::AsIs,
-            _ => SortOrder::RecentlyUpdated,
-        }
+/// Slugs are lowercase words of letters and digits separated by dashes
+pub static SLUG_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^[a-z0-9]+(-[a-z0-9]+)*$").unwrap());
+
+/// Length of the random suffix which makes generated slugs unique and hard to guess
+const SLUG_SUFFIX_LENGTH: usize = 8;
+
+/// Generates a slug from the words of the title followed by a random suffix, such as
+/// `rust-news-x7k2m9qa`
+pub fn generate_slug(title: &str) -> String {
+    let words = title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_lowercase())
+        .take(6)
+        .collect::<Vec<_>>();
+    let suffix = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(SLUG_SUFFIX_LENGTH)
+        .map(|c| char::from(c).to_ascii_lowercase())
+        .collect::<String>();
+
+    match words.is_empty() {
+        true => suffix,
+        false => format!("{}-{}", words.join("-"), suffix),
     }
 }
 
-impl From<String> for SortOrder {
-    fn from(s: String) -> Self {
-        SortOrder::from(s.as_str())
-    }
+/// Order of the items of a search or an index
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "sort_order", rename_all = "snake_case")]
+pub enum SortOrder {
+    /// Most recently updated first
+    RecentlyUpdated,
+    /// In the order of the items in their feed
+    AsIs,
+    /// Most recently published first
+    Published,
+    /// Least recently published first
+    Oldest,
+    /// Most relevant to the words and phrases of the query first, then most recently published
+    Relevance,
+    /// Shuffled, in the same order for every page of a timeline
+    Random,
 }
 
-impl Display for SortOrder {
-    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = match self {
-            SortOrder::RecentlyUpdated => "recently_updated".to_string(),
-            SortOrder::AsIs => "as_is".to_string(),
-        };
-        write!(formatter, "{}", s)
+/// When an item was published, falling back to when it was added for feeds without dates
+pub const PUBLISHED_AT_SQL: &str = "COALESCE(feed_item.published_at, feed_item.created_at)";
+
+impl SortOrder {
+    /// Returns the ordering of `feed_item` rows for the sort order, adding the parameters of
+    /// its keys. `seed` decides the order of random sorts, so it must stay the same between
+    /// pages
+    pub fn row_order(&self, query: &Query, seed: &str, params: &mut SqlParams) -> RowOrder {
+        match self {
+            SortOrder::RecentlyUpdated => RowOrder::new("feed_item.id", true)
+                .key("feed_item.updated_at", SortKeyType::Timestamp),
+            SortOrder::AsIs => RowOrder::new("feed_item.id", false)
+                .key("feed_item.index_in_feed", SortKeyType::Integer),
+            SortOrder::Published => {
+                RowOrder::new("feed_item.id", true).key(PUBLISHED_AT_SQL, SortKeyType::Timestamp)
+            }
+            SortOrder::Oldest => {
+                RowOrder::new("feed_item.id", false).key(PUBLISHED_AT_SQL, SortKeyType::Timestamp)
+            }
+            SortOrder::Relevance => {
+                let order = RowOrder::new("feed_item.id", true)
+                    .key(PUBLISHED_AT_SQL, SortKeyType::Timestamp);
+                match query.rank_sql(params) {
+                    Some(rank) => order.key_first(rank, SortKeyType::Real),
+                    None => order,
+                }
+            }
+            SortOrder::Random => RowOrder::new("feed_item.id", false).key(
+                format!("md5(feed_item.id::text || {})", params.push(seed)),
+                SortKeyType::Text,
+            ),
+        }
     }
 }
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::feed::daemon::{Daemon, FeedCreationError};
+
+use super::{Feed, Icon};
+
+/// Represents the subscription of a user to a feed. The title and icon are the ones the user
+/// sees for the feed, which start as the feed's own and can be customized
+#[derive(Clone, Debug, Serialize, Deserialize, sqlx::FromRow, ormx::Table, ToSchema)]
+#[ormx(table = "user_feed", id = id, insertable, deletable)]
+pub struct UserFeed {
+    #[ormx(default)]
+    pub id: i32,
+    pub owner: i32,
+    /// The id of the feed subscribed to
+    pub feed: i32,
+    pub title: String,
+    #[ormx(custom_type, by_ref)]
+    pub icon: Option<Icon>,
+    #[ormx(default)]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[ormx(default, set)]
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl UserFeed {
+    /// Subscribes the user to the feed at the link, creating the feed if needed. Returns the
+    /// subscription and whether it is new, since an existing one is returned unchanged
+    pub async fn subscribe(
+        pool: &sqlx::PgPool,
+        owner: i32,
+        link: &str,
+        title: Option<String>,
+        icon: Option<Icon>,
+    ) -> Result<(Self, bool), FeedCreationError> {
+        let feed = match Feed::get_by_link(pool, &link.to_string()).await? {
+            Some(feed) => feed,
+            None => Daemon::create_feed(pool, link).await?,
+        };
+        // Inserts in one statement, since checking first would race with concurrent requests
+        let subscription = sqlx::query_as!(
+            UserFeed,
+            r#"
+            INSERT INTO user_feed (owner, feed, title, icon) VALUES ($1, $2, $3, $4)
+            ON CONFLICT (owner, feed) DO NOTHING
+            RETURNING id, owner, feed, title, icon as "icon:Icon", created_at, updated_at
+            "#,
+            owner,
+            feed.id,
+            title.unwrap_or(feed.title),
+            icon as Option<Icon>
+        )
+        .fetch_optional(pool)
+        .await?;
+        if let Some(subscription) = subscription {
+            return Ok((subscription, true));
+        }
+
+        let subscription = Self::get_by_owner_and_feed(pool, owner, feed.id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+        Ok((subscription, false))
+    }
+
+    /// Returns the user's subscription to the feed, if they are subscribed
+    pub async fn get_by_owner_and_feed(
+        pool: &sqlx::PgPool,
+        owner: i32,
+        feed: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            UserFeed,
+            r#"
+            SELECT id, owner, feed, title, icon as "icon:Icon", created_at, updated_at
+            FROM user_feed WHERE owner = $1 AND feed = $2
+            "#,
+            owner,
+            feed
+        )
+        .fetch_optional(pool)
+        .await
+    }
+}
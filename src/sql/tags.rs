@@ -31,3 +31,84 @@ pub struct UserTag {
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
+
+impl UserTag {
+    /// Adds the feeds to the user's tag with the title, ignoring case, or to a new tag if they
+    /// don't have one. Returns the id of the tag
+    pub async fn add_feeds(
+        pool: &sqlx::PgPool,
+        owner: i32,
+        title: &str,
+        feeds: &[i32],
+    ) -> sqlx::Result<i32> {
+        let existing = sqlx::query_scalar!(
+            "SELECT id FROM user_tag WHERE owner = $1 AND lower(title) = lower($2) ORDER BY id LIMIT 1",
+            owner,
+            title
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        match existing {
+            Some(id) => {
+                sqlx::query!(
+                    r#"
+                    UPDATE user_tag SET
+                        children = children || ARRAY(
+                            SELECT DISTINCT ROW('feed', feed)::tag_child
+                            FROM unnest($2::integer[]) AS feed
+                            WHERE ROW('feed', feed)::tag_child <> ALL(children)
+                        ),
+                        updated_at = NOW()
+                    WHERE id = $1
+                    "#,
+                    id,
+                    feeds
+                )
+                .execute(pool)
+                .await?;
+                Ok(id)
+            }
+            None => {
+                sqlx::query_scalar!(
+                    r#"
+                    INSERT INTO user_tag (owner, title, children)
+                    SELECT $1, $2, ARRAY(
+                        SELECT DISTINCT ROW('feed', feed)::tag_child FROM unnest($3::integer[]) AS feed
+                    )
+                    RETURNING id
+                    "#,
+                    owner,
+                    title,
+                    feeds
+                )
+                .fetch_one(pool)
+                .await
+            }
+        }
+    }
+
+    /// Removes the feed from the user's tag with the title, ignoring case. Returns whether the
+    /// tag contained the feed
+    pub async fn remove_feed(
+        pool: &sqlx::PgPool,
+        owner: i32,
+        title: &str,
+        feed: i32,
+    ) -> sqlx::Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE user_tag SET
+                children = array_remove(children, ROW('feed', $3)::tag_child),
+                updated_at = NOW()
+            WHERE owner = $1 AND lower(title) = lower($2) AND ROW('feed', $3)::tag_child = ANY(children)
+            "#,
+            owner,
+            title,
+            feed
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{fetch_page, CursorPagination, FeedItem, Page, RowOrder};
+use crate::query::SqlParams;
+
+/// A feed item as shown in a user's timeline, with the feed it belongs to and whether the
+/// user has read or starred it
+#[derive(Clone, Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineItem {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub item: FeedItem,
+    /// Title of the feed the item belongs to
+    pub feed_title: String,
+    /// Domain of the feed the item belongs to
+    pub feed_domain: Option<String>,
+    /// Whether the user has read the item
+    pub read: bool,
+    /// Whether the user has starred the item
+    pub starred: bool,
+    /// When the user starred the item
+    pub starred_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Returns a query of the IDs of the feeds the user is subscribed to, either directly or
+/// through one of their tags
+pub fn subscribed_feed_ids_sql(user: &str) -> String {
+    format!(
+        "SELECT user_feed.feed FROM user_feed WHERE user_feed.owner = {0} \
+        UNION SELECT (child).id FROM user_tag, unnest(user_tag.children) AS child \
+        WHERE user_tag.owner = {0} AND (child).type = 'feed'",
+        user
+    )
+}
+
+/// Returns a predicate over `feed_item` matching items from the feeds the user is subscribed
+/// to, either directly or through one of their tags
+pub fn subscribed_feeds_sql(user: &str) -> String {
+    format!("feed_item.feed_id IN ({})", subscribed_feed_ids_sql(user))
+}
+
+impl TimelineItem {
+    /// Fetches a page of the items matching the condition, a predicate over `feed_item` whose
+    /// parameters have been added to `params`, along with the user's state of each item
+    pub async fn fetch_page(
+        pool: &sqlx::PgPool,
+        user_id: i32,
+        condition: &str,
+        order: &RowOrder,
+        pagination: &CursorPagination,
+        mut params: SqlParams,
+    ) -> sqlx::Result<Page<Self>> {
+        let from = format!(
+            "feed_item \
+            JOIN feed ON feed.id = feed_item.feed_id \
+            LEFT JOIN user_item_state ON user_item_state.item = feed_item.id \
+            AND user_item_state.owner = {}::integer",
+            params.push(user_id)
+        );
+
+        fetch_page(
+            pool,
+            "feed_item.*, feed.title AS feed_title, feed.domain AS feed_domain, \
+            COALESCE(user_item_state.read, false) AS read, \
+            COALESCE(user_item_state.starred, false) AS starred, \
+            user_item_state.starred_at",
+            &from,
+            condition,
+            order,
+            pagination,
+            params,
+        )
+        .await
+    }
+}
@@ -1,6 +1,8 @@
+use md5::Md5;
 use rand::{rng, Rng};
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use utoipa::ToSchema;
 
 use crate::http::common::Origins;
@@ -140,3 +142,57 @@ impl PasswordVerificationCode {
         generate_code()
     }
 }
+
+/// Key of a user for the Fever API, stored as the SHA-256 of the md5 clients send
+pub struct UserFeverKey;
+
+impl UserFeverKey {
+    /// Returns the hash stored for the key. Keys are hashed before reaching the database, so
+    /// they can't end up in its logs
+    fn hash(api_key: &str) -> String {
+        format!("{:x}", Sha256::digest(api_key.to_lowercase()))
+    }
+
+    /// Sets the key of the user to md5("<email>:<password>"), replacing any previous one
+    pub async fn set(
+        pool: &sqlx::PgPool,
+        owner: i32,
+        email: &str,
+        password: &str,
+    ) -> sqlx::Result<()> {
+        let api_key = format!("{:x}", Md5::digest(format!("{}:{}", email, password)));
+        sqlx::query!(
+            r#"
+            INSERT INTO user_fever_key (owner, api_key_hash) VALUES ($1, $2)
+            ON CONFLICT (owner) DO UPDATE SET
+                api_key_hash = EXCLUDED.api_key_hash,
+                created_at = NOW()
+            "#,
+            owner,
+            Self::hash(&api_key)
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Deletes the key of the user, returning whether they had one
+    pub async fn revoke(pool: &sqlx::PgPool, owner: i32) -> sqlx::Result<bool> {
+        let result = sqlx::query!("DELETE FROM user_fever_key WHERE owner = $1", owner)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns the user with the key, as sent by clients
+    pub async fn get_owner(pool: &sqlx::PgPool, api_key: &str) -> sqlx::Result<Option<i32>> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT owner FROM user_fever_key WHERE api_key_hash = $1
+            "#,
+            Self::hash(api_key)
+        )
+        .fetch_optional(pool)
+        .await
+    }
+}
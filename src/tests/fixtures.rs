@@ -0,0 +1,106 @@
+use ormx::Insert;
+
+use crate::sql::{
+    generate_slug, Icon, InsertUser, InsertUserIndex, SortOrder, User, UserIndex, Visibility,
+};
+
+/// Inserts the user which requests are authenticated as in tests (id 1 in a fresh database)
+pub async fn insert_user(pool: &sqlx::PgPool) -> User {
+    InsertUser {
+        email: "test@example.com".to_string(),
+        username: "test".to_string(),
+        providers: vec![],
+        password_hash: None,
+    }
+    .insert(pool)
+    .await
+    .unwrap()
+}
+
+/// Inserts a minimal RSS feed, returning its id
+pub async fn insert_feed(pool: &sqlx::PgPool, link: &str, title: &str) -> i32 {
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO feed (format, link, title, description)
+        VALUES ('rss', $1, $2, '')
+        RETURNING id
+        "#,
+        link,
+        title
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+/// Inserts an item into the feed, using the title as the guid, returning its id
+pub async fn insert_item(pool: &sqlx::PgPool, feed: i32, title: &str, description: &str) -> i64 {
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO feed_item (feed_id, guid, index_in_feed, title, description)
+        VALUES ($1, $2, 0, $2, $3)
+        RETURNING id
+        "#,
+        feed,
+        title,
+        description
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+/// Subscribes the user to the feed
+pub async fn insert_subscription(pool: &sqlx::PgPool, owner: i32, feed: i32) {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_feed (owner, feed, title)
+        SELECT $1, feed.id, feed.title FROM feed WHERE feed.id = $2
+        "#,
+        owner,
+        feed
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// Inserts a tag containing the feeds, returning its id
+pub async fn insert_tag(pool: &sqlx::PgPool, owner: i32, title: &str, feeds: &[i32]) -> i32 {
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO user_tag (owner, title, children)
+        SELECT $1, $2, COALESCE(array_agg(ROW('feed', feed)::tag_child), '{}')
+        FROM unnest($3::integer[]) AS feed
+        RETURNING id
+        "#,
+        owner,
+        title,
+        feeds
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+/// Inserts a private index with the query, titled after it
+pub async fn insert_index(
+    pool: &sqlx::PgPool,
+    owner: i32,
+    query: &str,
+    sort: SortOrder,
+) -> UserIndex {
+    InsertUserIndex {
+        owner,
+        query: query.to_string(),
+        sort,
+        title: query.to_string(),
+        description: None,
+        icon: Icon::get_random_icon(),
+        visibility: Visibility::Private,
+        slug: generate_slug(query),
+    }
+    .insert(pool)
+    .await
+    .unwrap()
+}
@@ -1,5 +1,6 @@
 pub mod context;
 pub mod dates;
+pub mod fixtures;
 pub mod sql;
 
 pub use context::TestContext;